@@ -0,0 +1,159 @@
+use crate::graph::{Graph, Node};
+use anyhow::Result;
+use std::{future::Future, pin::Pin};
+use vorpal_sdk::{
+    api::artifact::{ArtifactSource, ArtifactStep, ArtifactSystem},
+    artifact::{self, step, Artifact},
+    context::ConfigContext,
+};
+
+#[cfg(test)]
+pub mod mock;
+
+/// A builder from another crate running against the live context.
+pub type External<'a> = Pin<Box<dyn Future<Output = Result<String>> + 'a>>;
+
+/// Everything the builders in this crate ask of Vorpal. `VorpalContext`
+/// answers by talking to the services; tests use a context that only
+/// records what it is asked. Both add every artifact they register to
+/// their graph.
+pub trait BuildContext {
+    fn get_system(&self) -> ArtifactSystem;
+
+    /// Every artifact registered through this context so far.
+    fn graph(&mut self) -> &mut Graph;
+
+    /// A bash step, as `step::shell` builds one.
+    fn shell(
+        &mut self,
+        artifacts: Vec<String>,
+        environments: Vec<String>,
+        script: String,
+    ) -> impl Future<Output = Result<ArtifactStep>>;
+
+    /// Registers an artifact and returns its digest.
+    fn artifact(
+        &mut self,
+        name: &str,
+        steps: Vec<ArtifactStep>,
+        sources: Vec<ArtifactSource>,
+        systems: Vec<ArtifactSystem>,
+    ) -> impl Future<Output = Result<String>>;
+
+    /// Registers an artifact with a builder from another crate, which only
+    /// takes a live `ConfigContext`. `name` is what the graph and tests know
+    /// it by.
+    fn external<'a, F>(&'a mut self, name: &str, build: F) -> impl Future<Output = Result<String>>
+    where
+        F: FnOnce(&'a mut ConfigContext) -> External<'a>;
+
+    /// Registers the user environment: what it depends on, what it exports
+    /// and the symlinks Vorpal's activation creates.
+    fn user_environment(
+        &mut self,
+        name: &str,
+        systems: Vec<ArtifactSystem>,
+        artifacts: Vec<String>,
+        environments: Vec<String>,
+        symlinks: Vec<(String, String)>,
+    ) -> impl Future<Output = Result<String>>;
+}
+
+/// The live context, with the graph of what this evaluation registered.
+/// The SDK keeps its own store behind `ConfigContext` and exposes no graph.
+pub struct VorpalContext {
+    context: ConfigContext,
+    graph: Graph,
+}
+
+impl VorpalContext {
+    pub fn new(context: ConfigContext) -> Self {
+        Self {
+            context,
+            graph: Graph::new(),
+        }
+    }
+
+    /// Serves the registered artifacts to Vorpal.
+    pub async fn run(mut self) -> Result<()> {
+        self.context.run().await
+    }
+}
+
+impl BuildContext for VorpalContext {
+    fn get_system(&self) -> ArtifactSystem {
+        self.context.get_system()
+    }
+
+    fn graph(&mut self) -> &mut Graph {
+        &mut self.graph
+    }
+
+    async fn shell(
+        &mut self,
+        artifacts: Vec<String>,
+        environments: Vec<String>,
+        script: String,
+    ) -> Result<ArtifactStep> {
+        step::shell(&mut self.context, artifacts, environments, script, vec![]).await
+    }
+
+    async fn artifact(
+        &mut self,
+        name: &str,
+        steps: Vec<ArtifactStep>,
+        sources: Vec<ArtifactSource>,
+        systems: Vec<ArtifactSystem>,
+    ) -> Result<String> {
+        let node = Node::artifact(name, &steps, &sources);
+
+        let digest = Artifact::new(name, steps, systems)
+            .with_sources(sources)
+            .build(&mut self.context)
+            .await?;
+
+        self.graph.record(&digest, node);
+
+        Ok(digest)
+    }
+
+    async fn external<'a, F>(&'a mut self, name: &str, build: F) -> Result<String>
+    where
+        F: FnOnce(&'a mut ConfigContext) -> External<'a>,
+    {
+        let Self { context, graph } = self;
+
+        let digest = build(context).await?;
+
+        graph.record(&digest, Node::new(name));
+
+        Ok(digest)
+    }
+
+    async fn user_environment(
+        &mut self,
+        name: &str,
+        systems: Vec<ArtifactSystem>,
+        artifacts: Vec<String>,
+        environments: Vec<String>,
+        symlinks: Vec<(String, String)>,
+    ) -> Result<String> {
+        let symlinks = symlinks
+            .iter()
+            .map(|(source, target)| (source.as_str(), target.as_str()))
+            .collect();
+
+        let node = Node::new(name).with_inputs(&artifacts);
+
+        let digest = artifact::UserEnvironment::new(name, systems)
+            .with_artifacts(artifacts)
+            .with_environments(environments)
+            .with_symlinks(symlinks)
+            .build(&mut self.context)
+            .await?;
+
+        self.graph.record(&digest, node);
+
+        Ok(digest)
+    }
+}
@@ -0,0 +1,158 @@
+use super::{BuildContext, External};
+use crate::graph::{Graph, Node};
+use anyhow::Result;
+use vorpal_sdk::{
+    api::artifact::{ArtifactSource, ArtifactStep, ArtifactSystem},
+    context::ConfigContext,
+};
+
+/// One artifact a builder registered. External artifacts have neither steps
+/// nor sources: their builders never run offline.
+#[derive(Clone, Debug)]
+pub struct MockArtifact {
+    pub external: bool,
+    pub name: String,
+    pub sources: Vec<ArtifactSource>,
+    pub steps: Vec<ArtifactStep>,
+    pub systems: Vec<ArtifactSystem>,
+}
+
+#[derive(Clone, Debug)]
+pub struct MockUserEnvironment {
+    pub artifacts: Vec<String>,
+    pub environments: Vec<String>,
+    pub name: String,
+    pub symlinks: Vec<(String, String)>,
+}
+
+/// A context that contacts no Vorpal service. It records what builders
+/// register and hands back each artifact's name as its digest, so
+/// assertions can name what they look for.
+#[derive(Debug)]
+pub struct MockContext {
+    pub artifacts: Vec<MockArtifact>,
+    pub graph: Graph,
+    pub system: ArtifactSystem,
+    pub user_environments: Vec<MockUserEnvironment>,
+}
+
+impl MockContext {
+    pub fn new(system: ArtifactSystem) -> Self {
+        Self {
+            artifacts: vec![],
+            graph: Graph::new(),
+            system,
+            user_environments: vec![],
+        }
+    }
+
+    /// The artifact registered as `digest`. Panics if there is none, which
+    /// in a test is the failure to report.
+    pub fn registered(&self, digest: &str) -> &MockArtifact {
+        self.artifacts
+            .iter()
+            .find(|artifact| artifact.name == digest)
+            .unwrap_or_else(|| panic!("no artifact {digest} was registered"))
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.artifacts
+            .iter()
+            .map(|artifact| artifact.name.as_str())
+            .collect()
+    }
+}
+
+impl MockArtifact {
+    /// Every step's script, in order.
+    pub fn script(&self) -> String {
+        self.steps
+            .iter()
+            .filter_map(|step| step.script.clone())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl BuildContext for MockContext {
+    fn get_system(&self) -> ArtifactSystem {
+        self.system
+    }
+
+    fn graph(&mut self) -> &mut Graph {
+        &mut self.graph
+    }
+
+    async fn shell(
+        &mut self,
+        artifacts: Vec<String>,
+        environments: Vec<String>,
+        script: String,
+    ) -> Result<ArtifactStep> {
+        Ok(ArtifactStep {
+            artifacts,
+            environments,
+            script: Some(script),
+            ..Default::default()
+        })
+    }
+
+    async fn artifact(
+        &mut self,
+        name: &str,
+        steps: Vec<ArtifactStep>,
+        sources: Vec<ArtifactSource>,
+        systems: Vec<ArtifactSystem>,
+    ) -> Result<String> {
+        self.graph
+            .record(name, Node::artifact(name, &steps, &sources));
+
+        self.artifacts.push(MockArtifact {
+            external: false,
+            name: name.to_string(),
+            sources,
+            steps,
+            systems,
+        });
+
+        Ok(name.to_string())
+    }
+
+    async fn external<'a, F>(&'a mut self, name: &str, _build: F) -> Result<String>
+    where
+        F: FnOnce(&'a mut ConfigContext) -> External<'a>,
+    {
+        self.graph.record(name, Node::new(name));
+
+        self.artifacts.push(MockArtifact {
+            external: true,
+            name: name.to_string(),
+            sources: vec![],
+            steps: vec![],
+            systems: vec![self.system],
+        });
+
+        Ok(name.to_string())
+    }
+
+    async fn user_environment(
+        &mut self,
+        name: &str,
+        _systems: Vec<ArtifactSystem>,
+        artifacts: Vec<String>,
+        environments: Vec<String>,
+        symlinks: Vec<(String, String)>,
+    ) -> Result<String> {
+        self.graph
+            .record(name, Node::new(name).with_inputs(&artifacts));
+
+        self.user_environments.push(MockUserEnvironment {
+            artifacts,
+            environments,
+            name: name.to_string(),
+            symlinks,
+        });
+
+        Ok(name.to_string())
+    }
+}
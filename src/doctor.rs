@@ -0,0 +1,54 @@
+use anyhow::{bail, Context, Result};
+use dotfiles::{
+    health::{host_system, Outcome, Report},
+    user::{config::Config, health_checks},
+};
+use std::{env, path::PathBuf, process::ExitCode};
+
+/// Runs every check the configured components declare against the activated
+/// environment and prints pass, warn or fail for each, with what to do about
+/// anything that did not pass. `--json` prints the reports as a JSON array
+/// instead. Exits non-zero when any check fails; warnings alone do not.
+fn main() -> Result<ExitCode> {
+    let json = match env::args().skip(1).collect::<Vec<_>>().as_slice() {
+        [] => false,
+        [flag] if flag == "--json" => true,
+        _ => bail!("usage: dotfiles-doctor [--json]"),
+    };
+
+    let home = PathBuf::from(env::var_os("HOME").context("HOME is not set")?);
+    let path = env::var_os("PATH").unwrap_or_default();
+
+    let system = host_system()?;
+
+    let reports: Vec<Report> = health_checks(&Config::load()?, system)?
+        .iter()
+        .map(|(component, check)| check.run(component.name(), &home, &path))
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+    } else {
+        for report in &reports {
+            println!(
+                "{:<4}  {:<11}  {} ({})",
+                report.outcome.as_str(),
+                report.component,
+                report.name,
+                report.detail
+            );
+
+            if let Some(remediation) = &report.remediation {
+                println!("{:<19}fix: {remediation}", "");
+            }
+        }
+    }
+
+    let failed = reports.iter().any(|report| report.outcome == Outcome::Fail);
+
+    Ok(if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
@@ -0,0 +1,50 @@
+use anyhow::{bail, Context, Result};
+use dotfiles::{
+    manifest::{self, STATE_MANIFEST_PATH},
+    store::Store,
+};
+use std::{env, fs, path::PathBuf, process::ExitCode};
+
+/// Compares every target the last activation installed with what is in the
+/// home directory now, without changing anything. Exits non-zero when any
+/// target has drifted.
+///
+/// `--manifest <path>` checks another manifest instead, such as the one a
+/// fresh build ships under `share/dotfiles/manifest`.
+fn main() -> Result<ExitCode> {
+    let home = PathBuf::from(env::var_os("HOME").context("HOME is not set")?);
+
+    let mut args = env::args().skip(1);
+
+    let path = match (args.next().as_deref(), args.next(), args.next()) {
+        (None, _, _) => home.join(STATE_MANIFEST_PATH),
+        (Some("--manifest"), Some(path), None) => PathBuf::from(path),
+        _ => bail!("usage: dotfiles-drift [--manifest <path>]"),
+    };
+
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("{}: run `just activate` first", path.display()))?;
+
+    let entries = manifest::parse(&content).with_context(|| path.display().to_string())?;
+
+    let store = Store::from_env();
+    let mut drifted = 0;
+
+    for entry in &entries {
+        let status = entry.status(&home, &store)?;
+
+        if status.is_drift() {
+            drifted += 1;
+        }
+
+        println!("{:<10} {} ({status})", entry.mode.as_str(), entry.target);
+    }
+
+    println!("{drifted} of {} targets drifted", entries.len());
+
+    Ok(if drifted == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
@@ -0,0 +1,434 @@
+use crate::store::{Store, StorePath};
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+use vorpal_sdk::api::artifact::ArtifactSystem;
+
+/// One directory on `PATH`, or a path-valued variable.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PathEntry {
+    /// A directory spelled out in full, such as an app bundle on macOS.
+    Absolute(String),
+    /// The `bin` directory of a built artifact, resolved in the store.
+    ArtifactBin(String),
+    /// A directory relative to the activating user's home.
+    Home(String),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Value {
+    Literal(String),
+    Path(PathEntry),
+}
+
+/// A shell an environment script is written for. Bash and zsh read the POSIX
+/// syntax too, but each gets its own file so a shell's startup file sources
+/// one named after it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Shell {
+    Bash,
+    Fish,
+    Posix,
+    Zsh,
+}
+
+pub const SHELLS: [Shell; 4] = [Shell::Bash, Shell::Fish, Shell::Posix, Shell::Zsh];
+
+/// Everything `UserEnvironment` exports to the shell: variables and `PATH`
+/// entries, each optionally limited to some systems. Components add to it
+/// independently, and the conflicts between them surface when it renders.
+#[derive(Clone, Debug, Default)]
+pub struct Environment {
+    path: Vec<(PathEntry, Option<Vec<ArtifactSystem>>)>,
+    vars: Vec<(String, Value)>,
+}
+
+/// Escapes text for the inside of a double-quoted word, where `$`,
+/// backslashes and the quote itself are still live, as are backticks outside
+/// fish.
+fn escape_double_quoted(text: &str, shell: Shell) -> String {
+    text.chars()
+        .flat_map(|c| match c {
+            '"' | '$' | '\\' => vec!['\\', c],
+            '`' if shell != Shell::Fish => vec!['\\', c],
+            _ => vec![c],
+        })
+        .collect()
+}
+
+impl Shell {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Fish => "fish",
+            Shell::Posix => "sh",
+            Shell::Zsh => "zsh",
+        }
+    }
+
+    /// The home directory as the shell expands it inside double quotes. Fish
+    /// has no `${NAME}` form.
+    fn home(self) -> &'static str {
+        match self {
+            Shell::Fish => "$HOME",
+            _ => "${HOME}",
+        }
+    }
+}
+
+impl PathEntry {
+    pub fn absolute(path: &str) -> Self {
+        PathEntry::Absolute(path.to_string())
+    }
+
+    pub fn artifact_bin(store: &Store, output: &StorePath) -> Self {
+        PathEntry::ArtifactBin(store.resolve(&output.join("bin")).display().to_string())
+    }
+
+    pub fn home(path: &str) -> Self {
+        PathEntry::Home(path.trim_start_matches('/').to_string())
+    }
+
+    /// The entry as it goes inside a double-quoted word. Only the home
+    /// directory is left for the shell to expand.
+    pub fn render(&self, shell: Shell) -> Result<String> {
+        match self {
+            PathEntry::Absolute(path) => {
+                if !path.starts_with('/') {
+                    bail!("absolute PATH entry {path} does not start with /");
+                }
+
+                Ok(escape_double_quoted(path, shell))
+            }
+            PathEntry::ArtifactBin(path) => Ok(escape_double_quoted(path, shell)),
+            PathEntry::Home(path) if path.is_empty() => Ok(shell.home().to_string()),
+            PathEntry::Home(path) => Ok(format!(
+                "{}/{}",
+                shell.home(),
+                escape_double_quoted(path, shell)
+            )),
+        }
+    }
+}
+
+impl Value {
+    pub fn literal(value: &str) -> Self {
+        Value::Literal(value.to_string())
+    }
+
+    pub fn render(&self, shell: Shell) -> Result<String> {
+        match self {
+            Value::Literal(value) => Ok(escape_double_quoted(value, shell)),
+            Value::Path(entry) => entry.render(shell),
+        }
+    }
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_path(mut self, entry: PathEntry) -> Self {
+        self.path.push((entry, None));
+        self
+    }
+
+    /// A `PATH` entry that exists only on some systems, such as an app
+    /// bundle installed outside the store.
+    pub fn with_path_on(mut self, entry: PathEntry, systems: &[ArtifactSystem]) -> Self {
+        self.path.push((entry, Some(systems.to_vec())));
+        self
+    }
+
+    pub fn with_var(mut self, key: &str, value: Value) -> Self {
+        self.vars.push((key.to_string(), value));
+        self
+    }
+
+    /// Everything another environment declares, after everything this one
+    /// does.
+    pub fn with_environment(mut self, other: Environment) -> Self {
+        self.path.extend(other.path);
+        self.vars.extend(other.vars);
+        self
+    }
+
+    /// Variables by name. Setting a variable twice to the same value is
+    /// harmless; setting it to two values is an error.
+    pub fn vars(&self) -> Result<BTreeMap<String, Value>> {
+        let mut vars: BTreeMap<String, Value> = BTreeMap::new();
+
+        for (key, value) in &self.vars {
+            if key == "PATH" {
+                bail!("PATH is built from path entries, not set as a variable");
+            }
+
+            if key.is_empty()
+                || key.starts_with(|c: char| c.is_ascii_digit())
+                || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            {
+                bail!("{key:?} is not a valid environment variable name");
+            }
+
+            match vars.get(key) {
+                Some(existing) if existing != value => {
+                    bail!("{key} is set to both {existing:?} and {value:?}")
+                }
+                Some(_) => {}
+                None => {
+                    vars.insert(key.clone(), value.clone());
+                }
+            }
+        }
+
+        Ok(vars)
+    }
+
+    /// `PATH` entries that apply to the system, first declaration first, each
+    /// listed once.
+    pub fn path(&self, system: ArtifactSystem) -> Vec<&PathEntry> {
+        let mut entries: Vec<&PathEntry> = vec![];
+
+        for (entry, systems) in &self.path {
+            let applies = systems
+                .as_ref()
+                .map(|systems| systems.contains(&system))
+                .unwrap_or(true);
+
+            if applies && !entries.contains(&entry) {
+                entries.push(entry);
+            }
+        }
+
+        entries
+    }
+
+    /// `KEY="value"` assignments for one system in key order, `PATH` last and
+    /// prepended to the inherited one.
+    pub fn render(&self, system: ArtifactSystem) -> Result<Vec<String>> {
+        let mut assignments = vec![];
+
+        for (key, value) in self.vars()? {
+            assignments.push(format!("{key}=\"{}\"", value.render(Shell::Posix)?));
+        }
+
+        let path = self.rendered_path(Shell::Posix, system)?;
+
+        if !path.is_empty() {
+            assignments.push(format!("PATH=\"{}:${{PATH}}\"", path.join(":")));
+        }
+
+        Ok(assignments)
+    }
+
+    fn rendered_path(&self, shell: Shell, system: ArtifactSystem) -> Result<Vec<String>> {
+        self.path(system)
+            .into_iter()
+            .map(|entry| entry.render(shell))
+            .collect()
+    }
+
+    /// A script for the shell to source that exports the environment for
+    /// one system.
+    pub fn script(&self, shell: Shell, system: ArtifactSystem) -> Result<String> {
+        let mut lines = vec![];
+
+        for (key, value) in self.vars()? {
+            let value = value.render(shell)?;
+
+            lines.push(match shell {
+                Shell::Fish => format!("set -gx {key} \"{value}\""),
+                _ => format!("export {key}=\"{value}\""),
+            });
+        }
+
+        let path = self.rendered_path(shell, system)?;
+
+        if !path.is_empty() {
+            lines.push(match shell {
+                Shell::Fish => format!(
+                    "set -gx PATH {} $PATH",
+                    path.iter()
+                        .map(|entry| format!("\"{entry}\""))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                ),
+                _ => format!("export PATH=\"{}:${{PATH}}\"", path.join(":")),
+            });
+        }
+
+        Ok(format!("{}\n", lines.join("\n")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Environment, PathEntry, Shell, Value};
+    use crate::store::{Store, StorePath};
+    use vorpal_sdk::api::artifact::ArtifactSystem::{Aarch64Darwin, X8664Linux};
+
+    #[test]
+    fn spaces_are_quoted_not_backslash_escaped() {
+        let environment = Environment::new().with_path(PathEntry::absolute(
+            "/Applications/VMware Fusion.app/Contents/Library",
+        ));
+
+        assert_eq!(
+            environment.render(Aarch64Darwin).unwrap(),
+            vec!["PATH=\"/Applications/VMware Fusion.app/Contents/Library:${PATH}\""]
+        );
+    }
+
+    #[test]
+    fn system_specific_entries_are_left_out_elsewhere() {
+        let environment = Environment::new()
+            .with_path_on(
+                PathEntry::absolute("/Applications/Obsidian.app"),
+                &[Aarch64Darwin],
+            )
+            .with_path(PathEntry::home(".local/bin"));
+
+        assert_eq!(
+            environment.render(X8664Linux).unwrap(),
+            vec!["PATH=\"${HOME}/.local/bin:${PATH}\""]
+        );
+    }
+
+    #[test]
+    fn repeated_path_entries_keep_their_first_position() {
+        let environment = Environment::new()
+            .with_path(PathEntry::home(".local/bin"))
+            .with_path(PathEntry::home("/.vorpal/bin"))
+            .with_path(PathEntry::home(".local/bin"));
+
+        assert_eq!(
+            environment.render(X8664Linux).unwrap(),
+            vec!["PATH=\"${HOME}/.local/bin:${HOME}/.vorpal/bin:${PATH}\""]
+        );
+    }
+
+    #[test]
+    fn artifact_bins_resolve_to_the_store() {
+        assert_eq!(
+            PathEntry::artifact_bin(
+                &Store::new("/scratch/store"),
+                &StorePath::output("8814b4d3fa73").with_namespace("user")
+            )
+            .render(Shell::Posix)
+            .unwrap(),
+            "/scratch/store/artifact/output/user/8814b4d3fa73/bin"
+        );
+    }
+
+    #[test]
+    fn an_artifact_bin_added_twice_is_listed_once() {
+        let store = Store::new("/scratch/store");
+        let output = StorePath::output("8814b4d3fa73");
+
+        let environment = Environment::new()
+            .with_path(PathEntry::artifact_bin(&store, &output))
+            .with_path(PathEntry::home(".local/bin"))
+            .with_path(PathEntry::artifact_bin(&store, &output));
+
+        assert_eq!(
+            environment.render(X8664Linux).unwrap(),
+            vec![
+                "PATH=\"/scratch/store/artifact/output/library/8814b4d3fa73/bin:${HOME}/.local/bin:${PATH}\""
+            ]
+        );
+    }
+
+    #[test]
+    fn shell_metacharacters_in_values_are_escaped() {
+        let environment = Environment::new().with_var("PAGER", Value::literal("less \"-R\" $X"));
+
+        assert_eq!(
+            environment.render(X8664Linux).unwrap(),
+            vec!["PAGER=\"less \\\"-R\\\" \\$X\""]
+        );
+    }
+
+    #[test]
+    fn the_same_value_set_twice_is_not_a_conflict() {
+        let environment = Environment::new()
+            .with_var("EDITOR", Value::literal("nvim"))
+            .with_environment(Environment::new().with_var("EDITOR", Value::literal("nvim")));
+
+        assert_eq!(
+            environment.render(X8664Linux).unwrap(),
+            vec!["EDITOR=\"nvim\""]
+        );
+    }
+
+    #[test]
+    fn two_values_for_one_variable_are_a_conflict() {
+        let environment = Environment::new()
+            .with_var("EDITOR", Value::literal("nvim"))
+            .with_var("EDITOR", Value::literal("vim"));
+
+        assert!(environment.render(X8664Linux).is_err());
+    }
+
+    #[test]
+    fn relative_absolute_entries_are_rejected() {
+        let environment = Environment::new().with_path(PathEntry::absolute("opt/bin"));
+
+        assert!(environment.render(X8664Linux).is_err());
+    }
+
+    fn sample() -> Environment {
+        Environment::new()
+            .with_var("EDITOR", Value::literal("nvim"))
+            .with_var(
+                "GOPATH",
+                Value::Path(PathEntry::home("Development/language/go")),
+            )
+            .with_path_on(
+                PathEntry::absolute("/Applications/VMware Fusion.app/Contents/Library"),
+                &[Aarch64Darwin],
+            )
+            .with_path(PathEntry::home(".local/bin"))
+    }
+
+    #[test]
+    fn posix_script_exports_each_variable_and_prepends_path() {
+        assert_eq!(
+            sample().script(Shell::Posix, Aarch64Darwin).unwrap(),
+            "export EDITOR=\"nvim\"\n\
+             export GOPATH=\"${HOME}/Development/language/go\"\n\
+             export PATH=\"/Applications/VMware Fusion.app/Contents/Library:${HOME}/.local/bin:${PATH}\"\n"
+        );
+    }
+
+    #[test]
+    fn bash_and_zsh_scripts_use_the_posix_syntax() {
+        let posix = sample().script(Shell::Posix, X8664Linux).unwrap();
+
+        assert_eq!(sample().script(Shell::Bash, X8664Linux).unwrap(), posix);
+        assert_eq!(sample().script(Shell::Zsh, X8664Linux).unwrap(), posix);
+    }
+
+    #[test]
+    fn fish_script_sets_globals_and_lists_path_entries() {
+        assert_eq!(
+            sample().script(Shell::Fish, Aarch64Darwin).unwrap(),
+            "set -gx EDITOR \"nvim\"\n\
+             set -gx GOPATH \"$HOME/Development/language/go\"\n\
+             set -gx PATH \"/Applications/VMware Fusion.app/Contents/Library\" \"$HOME/.local/bin\" $PATH\n"
+        );
+    }
+
+    #[test]
+    fn backticks_are_only_escaped_where_they_are_live() {
+        let environment = Environment::new().with_var("PS_HINT", Value::literal("`date`"));
+
+        assert_eq!(
+            environment.script(Shell::Posix, X8664Linux).unwrap(),
+            "export PS_HINT=\"\\`date\\`\"\n"
+        );
+        assert_eq!(
+            environment.script(Shell::Fish, X8664Linux).unwrap(),
+            "set -gx PS_HINT \"`date`\"\n"
+        );
+    }
+}
@@ -1,10 +1,7 @@
+use crate::{context::BuildContext, store::StorePath};
 use anyhow::Result;
 use indoc::formatdoc;
-use vorpal_sdk::{
-    api::artifact::ArtifactSystem,
-    artifact::{step, Artifact, ArtifactSource},
-    context::ConfigContext,
-};
+use vorpal_sdk::{api::artifact::ArtifactSystem, artifact::ArtifactSource};
 
 pub struct FileCreate {
     artifacts: Vec<String>,
@@ -15,6 +12,7 @@ pub struct FileCreate {
 }
 
 pub struct FileSource {
+    digest: Option<String>,
     name: String,
     path: String,
     systems: Vec<ArtifactSystem>,
@@ -70,14 +68,14 @@ impl FileCreate {
         self
     }
 
-    /// Path of the single file this artifact holds. `build` writes
+    /// The single file the artifact built as `digest` holds. `build` writes
     /// `$VORPAL_OUTPUT/{name}`, so the file is always named after the
     /// artifact: a symlink must point here, not at the output directory.
-    pub fn output_file_path(output: &str, name: &str) -> String {
-        format!("{output}/{name}")
+    pub fn output_file(digest: &str, name: &str) -> StorePath {
+        StorePath::output(digest).join(name)
     }
 
-    pub async fn build(self, context: &mut ConfigContext) -> Result<String> {
+    pub async fn build(self, context: &mut impl BuildContext) -> Result<String> {
         let chmod_mode = if self.executable { "755" } else { "644" };
 
         let step_script = formatdoc! {"
@@ -92,28 +90,38 @@ impl FileCreate {
             name = self.name,
         };
 
-        let step = step::shell(context, self.artifacts, vec![], step_script, vec![]).await?;
+        let step = context
+            .shell(self.artifacts.clone(), vec![], step_script)
+            .await?;
 
-        Artifact::new(
-            &format!("{}-file-create", self.name),
-            vec![step],
-            self.systems,
-        )
-        .build(context)
-        .await
+        let name = format!("{}-file-create", self.name);
+
+        let digest = context
+            .artifact(&name, vec![step], vec![], self.systems)
+            .await?;
+
+        Ok(digest)
     }
 }
 
 impl FileSource {
     pub fn new(name: &str, path: &str, systems: Vec<ArtifactSystem>) -> Self {
         Self {
+            digest: None,
             name: name.to_string(),
             path: path.to_string(),
             systems,
         }
     }
 
-    pub async fn build(self, context: &mut ConfigContext) -> Result<String> {
+    /// Pin the fetched source to a sha256 digest, so a remote path that
+    /// changes underneath fails the build instead of changing the artifact.
+    pub fn with_digest(mut self, digest: &str) -> Self {
+        self.digest = Some(digest.to_string());
+        self
+    }
+
+    pub async fn build(self, context: &mut impl BuildContext) -> Result<String> {
         let layout = SourceLayout::for_path(&self.path);
 
         let step_script = formatdoc! {r#"
@@ -124,20 +132,24 @@ impl FileSource {
             source_path = layout.source_path,
         };
 
-        let step = step::shell(context, vec![], vec![], step_script, vec![]).await?;
+        let step = context.shell(vec![], vec![], step_script).await?;
+
+        let mut source = ArtifactSource::new(&format!("{}-file-source", self.name), &layout.path)
+            .with_includes(layout.includes);
+
+        if let Some(digest) = &self.digest {
+            source = source.with_digest(digest);
+        }
+
+        let source = source.build();
+
+        let name = format!("{}-file-source", self.name);
 
-        let source = ArtifactSource::new(&format!("{}-file-source", self.name), &layout.path)
-            .with_includes(layout.includes)
-            .build();
+        let digest = context
+            .artifact(&name, vec![step], vec![source], self.systems)
+            .await?;
 
-        Artifact::new(
-            &format!("{}-file-source", self.name),
-            vec![step],
-            self.systems,
-        )
-        .with_sources(vec![source])
-        .build(context)
-        .await
+        Ok(digest)
     }
 }
 
@@ -176,8 +188,8 @@ mod tests {
     #[test]
     fn created_file_is_addressed_by_the_artifact_name_inside_the_output() {
         assert_eq!(
-            FileCreate::output_file_path("/store/output/user/abc123", "user-claude-code-settings"),
-            "/store/output/user/abc123/user-claude-code-settings"
+            FileCreate::output_file("abc123", "user-claude-code-settings").env_key(),
+            "$VORPAL_ARTIFACT_abc123/user-claude-code-settings"
         );
     }
 }
@@ -0,0 +1,340 @@
+use crate::install::Install;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::{collections::BTreeMap, fs, path::Path};
+use vorpal_sdk::api::artifact::{ArtifactSource, ArtifactStep};
+
+/// A directory to write `graph.dot` and `graph.json` into after the build
+/// is registered. Unset, nothing is written.
+pub const GRAPH_VAR: &str = "DOTFILES_GRAPH";
+
+/// One artifact: what it is called and what went into it. Inputs are the
+/// digests of artifacts its step reads; sources are fetched paths and URLs.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct Node {
+    pub inputs: Vec<String>,
+    pub name: String,
+    pub sources: Vec<String>,
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+struct NodeEntry<'a> {
+    digest: &'a str,
+    #[serde(flatten)]
+    node: &'a Node,
+}
+
+/// Where an artifact path ends up in `$HOME`.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct InstallEdge {
+    pub digest: String,
+    pub mode: &'static str,
+    pub path: Option<String>,
+    pub target: String,
+}
+
+/// Every artifact registered in one evaluation, kept by the context that
+/// registered them, and where the user environment installs them.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Graph {
+    installs: Vec<InstallEdge>,
+    nodes: BTreeMap<String, Node>,
+}
+
+impl Node {
+    pub fn new(name: &str) -> Self {
+        Self {
+            inputs: vec![],
+            name: name.to_string(),
+            sources: vec![],
+        }
+    }
+
+    /// An artifact as it is registered: the artifacts its steps read, each
+    /// once, and the paths its sources fetch.
+    pub fn artifact(name: &str, steps: &[ArtifactStep], sources: &[ArtifactSource]) -> Self {
+        let mut inputs: Vec<String> = vec![];
+
+        for input in steps.iter().flat_map(|step| &step.artifacts) {
+            if !inputs.contains(input) {
+                inputs.push(input.clone());
+            }
+        }
+
+        Self {
+            inputs,
+            name: name.to_string(),
+            sources: sources.iter().map(|source| source.path.clone()).collect(),
+        }
+    }
+
+    pub fn with_inputs(mut self, inputs: &[String]) -> Self {
+        self.inputs = inputs.to_vec();
+        self
+    }
+
+    pub fn with_sources(mut self, sources: &[&str]) -> Self {
+        self.sources = sources.iter().map(|source| source.to_string()).collect();
+        self
+    }
+}
+
+/// Quotes a DOT identifier or label.
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl Graph {
+    pub const fn new() -> Self {
+        Self {
+            installs: vec![],
+            nodes: BTreeMap::new(),
+        }
+    }
+
+    /// Adds an artifact. Building the same artifact twice yields the same
+    /// digest, so a repeat merges its inputs and sources into the first.
+    pub fn record(&mut self, digest: &str, node: Node) {
+        match self.nodes.get_mut(digest) {
+            Some(existing) => {
+                for input in node.inputs {
+                    if !existing.inputs.contains(&input) {
+                        existing.inputs.push(input);
+                    }
+                }
+
+                for source in node.sources {
+                    if !existing.sources.contains(&source) {
+                        existing.sources.push(source);
+                    }
+                }
+            }
+            None => {
+                self.nodes.insert(digest.to_string(), node);
+            }
+        }
+    }
+
+    pub fn node(&self, digest: &str) -> Option<&Node> {
+        self.nodes.get(digest)
+    }
+
+    /// Adds where the user environment installs its artifacts.
+    pub fn record_installs(&mut self, installs: &[Install]) {
+        self.installs
+            .extend(installs.iter().map(|install| InstallEdge {
+                digest: install.source.digest.clone(),
+                mode: install.mode.as_str(),
+                path: install.source.path.clone(),
+                target: install.target.tilde(),
+            }));
+        self.installs.sort();
+        self.installs.dedup();
+    }
+
+    /// Nodes in name order, then digest, so two builds diff line by line.
+    fn sorted_nodes(&self) -> Vec<(&str, &Node)> {
+        let mut nodes: Vec<(&str, &Node)> = self
+            .nodes
+            .iter()
+            .map(|(digest, node)| (digest.as_str(), node))
+            .collect();
+
+        nodes.sort_by(|a, b| (&a.1.name, a.0).cmp(&(&b.1.name, b.0)));
+        nodes
+    }
+
+    /// A label for a digest: its recorded name, or the digest itself for an
+    /// artifact some builder did not report.
+    fn label<'a>(&'a self, digest: &'a str) -> &'a str {
+        self.nodes
+            .get(digest)
+            .map(|node| node.name.as_str())
+            .unwrap_or(digest)
+    }
+
+    /// Graphviz: artifacts are ellipses labelled by name, sources notes and
+    /// install targets boxes. Edges point from what is used to its user.
+    pub fn dot(&self) -> String {
+        let mut lines = vec![
+            "digraph artifacts {".to_string(),
+            "    rankdir=LR;".to_string(),
+        ];
+
+        for (digest, node) in self.sorted_nodes() {
+            lines.push(format!(
+                "    {} [label={}];",
+                quote(digest),
+                quote(&node.name)
+            ));
+
+            for input in &node.inputs {
+                if !self.nodes.contains_key(input) {
+                    lines.push(format!("    {} [label={}];", quote(input), quote(input)));
+                }
+
+                lines.push(format!("    {} -> {};", quote(input), quote(digest)));
+            }
+
+            for source in &node.sources {
+                let id = format!("source:{source}");
+                lines.push(format!(
+                    "    {} [label={}, shape=note];",
+                    quote(&id),
+                    quote(source)
+                ));
+                lines.push(format!("    {} -> {};", quote(&id), quote(digest)));
+            }
+        }
+
+        for install in &self.installs {
+            lines.push(format!("    {} [shape=box];", quote(&install.target)));
+            lines.push(format!(
+                "    {} -> {} [label={}];",
+                quote(&install.digest),
+                quote(&install.target),
+                quote(install.mode)
+            ));
+        }
+
+        lines.push("}".to_string());
+        lines.join("\n") + "\n"
+    }
+
+    pub fn json(&self) -> Result<String> {
+        #[derive(Serialize)]
+        struct Document<'a> {
+            installs: Vec<InstallEdgeEntry<'a>>,
+            nodes: Vec<NodeEntry<'a>>,
+        }
+
+        #[derive(Serialize)]
+        struct InstallEdgeEntry<'a> {
+            name: &'a str,
+            #[serde(flatten)]
+            edge: &'a InstallEdge,
+        }
+
+        let document = Document {
+            installs: self
+                .installs
+                .iter()
+                .map(|edge| InstallEdgeEntry {
+                    name: self.label(&edge.digest),
+                    edge,
+                })
+                .collect(),
+            nodes: self
+                .sorted_nodes()
+                .into_iter()
+                .map(|(digest, node)| NodeEntry { digest, node })
+                .collect(),
+        };
+
+        Ok(serde_json::to_string_pretty(&document)? + "\n")
+    }
+
+    /// Writes `graph.dot` and `graph.json` for everything recorded so far.
+    pub fn write(&self, dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir).map_err(|error| anyhow!("{}: {error}", dir.display()))?;
+        fs::write(dir.join("graph.dot"), self.dot())?;
+        fs::write(dir.join("graph.json"), self.json()?)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Graph, Node};
+    use crate::{home::HomePath, install::Install, store::StorePath};
+    use vorpal_sdk::api::artifact::{ArtifactSource, ArtifactStep};
+
+    fn graph() -> Graph {
+        let mut graph = Graph::new();
+
+        graph.record(
+            "ccc",
+            Node::new("user-bat-theme").with_sources(&["https://example.com/theme"]),
+        );
+        graph.record("bbb", Node::new("user-bat-config"));
+        graph.record(
+            "aaa",
+            Node::new("user-activation").with_inputs(&["bbb".to_string(), "ddd".to_string()]),
+        );
+        graph.record_installs(&[Install::link(
+            StorePath::output("bbb").join("user-bat-config"),
+            HomePath::new(".config/bat/config").unwrap(),
+        )]);
+
+        graph
+    }
+
+    #[test]
+    fn repeated_artifacts_merge_their_inputs() {
+        let mut graph = Graph::new();
+
+        graph.record("aaa", Node::new("tool").with_inputs(&["x".to_string()]));
+        graph.record(
+            "aaa",
+            Node::new("tool").with_inputs(&["x".to_string(), "y".to_string()]),
+        );
+
+        assert_eq!(graph.nodes["aaa"].inputs, vec!["x", "y"]);
+    }
+
+    #[test]
+    fn registered_artifacts_read_their_steps_and_sources() {
+        let step = |artifacts: &[&str]| ArtifactStep {
+            artifacts: artifacts.iter().map(|a| a.to_string()).collect(),
+            ..Default::default()
+        };
+        let source = ArtifactSource {
+            path: "https://example.com/theme".to_string(),
+            ..Default::default()
+        };
+
+        let node = Node::artifact(
+            "user-bat-theme",
+            &[step(&["aaa", "bbb"]), step(&["bbb"])],
+            &[source],
+        );
+
+        assert_eq!(
+            node,
+            Node::new("user-bat-theme")
+                .with_inputs(&["aaa".to_string(), "bbb".to_string()])
+                .with_sources(&["https://example.com/theme"])
+        );
+    }
+
+    #[test]
+    fn dot_lists_nodes_by_name_with_unreported_inputs_by_digest() {
+        assert_eq!(
+            graph().dot(),
+            "digraph artifacts {\n    \
+             rankdir=LR;\n    \
+             \"aaa\" [label=\"user-activation\"];\n    \
+             \"bbb\" -> \"aaa\";\n    \
+             \"ddd\" [label=\"ddd\"];\n    \
+             \"ddd\" -> \"aaa\";\n    \
+             \"bbb\" [label=\"user-bat-config\"];\n    \
+             \"ccc\" [label=\"user-bat-theme\"];\n    \
+             \"source:https://example.com/theme\" [label=\"https://example.com/theme\", shape=note];\n    \
+             \"source:https://example.com/theme\" -> \"ccc\";\n    \
+             \"~/.config/bat/config\" [shape=box];\n    \
+             \"bbb\" -> \"~/.config/bat/config\" [label=\"link\"];\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn json_names_install_edges() {
+        let json: serde_json::Value = serde_json::from_str(&graph().json().unwrap()).unwrap();
+
+        assert_eq!(json["installs"][0]["name"], "user-bat-config");
+        assert_eq!(json["installs"][0]["target"], "~/.config/bat/config");
+        assert_eq!(json["nodes"][0]["digest"], "aaa");
+        assert_eq!(json["nodes"][0]["inputs"][1], "ddd");
+    }
+}
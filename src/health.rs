@@ -0,0 +1,340 @@
+use crate::home::HomePath;
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::{
+    env,
+    ffi::OsStr,
+    fs,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use vorpal_sdk::api::artifact::{
+    ArtifactSystem,
+    ArtifactSystem::{Aarch64Darwin, Aarch64Linux, X8664Darwin, X8664Linux},
+};
+
+/// What one check looks at. Every probe only reads, except that writability
+/// is proven by creating and removing a scratch file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Probe {
+    /// A directory that already exists or can be created, judged by its
+    /// nearest existing ancestor.
+    Creatable(HomePath),
+    /// A regular file that has to exist, such as one only `dotfiles-activate`
+    /// writes.
+    File(HomePath),
+    /// A program that has to be on `PATH`.
+    OnPath(String),
+    /// A program whose trimmed output has to name a path in `$HOME`.
+    PrintsHomePath {
+        args: Vec<String>,
+        expected: HomePath,
+        program: String,
+    },
+    /// A directory that has to exist and be writable.
+    Writable(HomePath),
+}
+
+/// How bad it is when a check does not pass.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Fail,
+    Warn,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Fail,
+    Pass,
+    Warn,
+}
+
+impl Outcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Outcome::Fail => "fail",
+            Outcome::Pass => "pass",
+            Outcome::Warn => "warn",
+        }
+    }
+}
+
+/// One health check a component declares about the activated environment.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Check {
+    pub name: String,
+    pub probe: Probe,
+    pub remediation: String,
+    pub severity: Severity,
+}
+
+/// The result of running one check, as printed or serialized.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Report {
+    pub component: String,
+    pub detail: String,
+    pub name: String,
+    pub outcome: Outcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+}
+
+/// The system `dotfiles-doctor` runs on, spelled as the artifacts are.
+pub fn host_system() -> Result<ArtifactSystem> {
+    match (env::consts::ARCH, env::consts::OS) {
+        ("aarch64", "macos") => Ok(Aarch64Darwin),
+        ("aarch64", "linux") => Ok(Aarch64Linux),
+        ("x86_64", "macos") => Ok(X8664Darwin),
+        ("x86_64", "linux") => Ok(X8664Linux),
+        (arch, os) => bail!("no artifact system for {arch}-{os}"),
+    }
+}
+
+/// The first executable called `program` in the `PATH` directories.
+fn find_on_path(program: &str, path: &OsStr) -> Option<PathBuf> {
+    env::split_paths(path)
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate))
+}
+
+fn is_executable(path: &Path) -> bool {
+    fs::metadata(path)
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+/// Whether a file can be created in `dir`, by creating and removing one.
+fn can_write_in(dir: &Path) -> bool {
+    let scratch = dir.join(format!(".dotfiles-doctor-{}", std::process::id()));
+
+    match fs::File::create(&scratch) {
+        Ok(_) => fs::remove_file(&scratch).is_ok(),
+        Err(_) => false,
+    }
+}
+
+impl Check {
+    /// A check whose failure breaks something the component promises.
+    pub fn fail(name: &str, probe: Probe, remediation: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            probe,
+            remediation: remediation.to_string(),
+            severity: Severity::Fail,
+        }
+    }
+
+    /// A check whose failure degrades the environment without breaking it.
+    pub fn warn(name: &str, probe: Probe, remediation: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            probe,
+            remediation: remediation.to_string(),
+            severity: Severity::Warn,
+        }
+    }
+
+    /// Runs the probe against `home` and the `PATH` value given, returning
+    /// whether it passed and what was seen.
+    fn probe(&self, home: &Path, path: &OsStr) -> (bool, String) {
+        match &self.probe {
+            Probe::Creatable(dir) => {
+                let target = home.join(dir.relative());
+
+                if target.is_dir() {
+                    return (true, format!("{dir} exists"));
+                }
+
+                match target
+                    .ancestors()
+                    .skip(1)
+                    .find(|ancestor| ancestor.exists())
+                {
+                    Some(ancestor) if ancestor.is_dir() && can_write_in(ancestor) => {
+                        (true, format!("{dir} can be created"))
+                    }
+                    Some(ancestor) => (
+                        false,
+                        format!("{dir} cannot be created in {}", ancestor.display()),
+                    ),
+                    None => (false, format!("{dir} has no existing ancestor")),
+                }
+            }
+            Probe::File(file) => {
+                if home.join(file.relative()).is_file() {
+                    (true, format!("{file} exists"))
+                } else {
+                    (false, format!("{file} is not a file"))
+                }
+            }
+            Probe::OnPath(program) => match find_on_path(program, path) {
+                Some(found) => (true, found.display().to_string()),
+                None => (false, format!("{program} is not on PATH")),
+            },
+            Probe::PrintsHomePath {
+                args,
+                expected,
+                program,
+            } => {
+                let Some(found) = find_on_path(program, path) else {
+                    return (false, format!("{program} is not on PATH"));
+                };
+
+                let output = match Command::new(found).args(args).env("PATH", path).output() {
+                    Ok(output) if output.status.success() => output,
+                    Ok(output) => return (false, format!("{program} exited {}", output.status)),
+                    Err(error) => return (false, format!("{program}: {error}")),
+                };
+
+                let printed = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                let expected_path = home.join(expected.relative());
+
+                if Path::new(&printed) == expected_path {
+                    (true, printed)
+                } else {
+                    (
+                        false,
+                        format!("{printed}, expected {}", expected_path.display()),
+                    )
+                }
+            }
+            Probe::Writable(dir) => {
+                let target = home.join(dir.relative());
+
+                if !target.is_dir() {
+                    (false, format!("{dir} does not exist"))
+                } else if can_write_in(&target) {
+                    (true, format!("{dir} is writable"))
+                } else {
+                    (false, format!("{dir} is not writable"))
+                }
+            }
+        }
+    }
+
+    pub fn run(&self, component: &str, home: &Path, path: &OsStr) -> Report {
+        let (passed, detail) = self.probe(home, path);
+
+        let outcome = match (passed, self.severity) {
+            (true, _) => Outcome::Pass,
+            (false, Severity::Fail) => Outcome::Fail,
+            (false, Severity::Warn) => Outcome::Warn,
+        };
+
+        Report {
+            component: component.to_string(),
+            detail,
+            name: self.name.clone(),
+            outcome,
+            remediation: (!passed).then(|| self.remediation.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Check, Outcome, Probe};
+    use crate::home::HomePath;
+    use std::{ffi::OsString, fs, os::unix::fs::PermissionsExt, path::PathBuf};
+
+    fn scratch(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("dotfiles-health-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("home")).unwrap();
+        fs::create_dir_all(root.join("bin")).unwrap();
+        root
+    }
+
+    #[test]
+    fn directories_pass_when_present_or_creatable() {
+        let root = scratch("directories");
+        let home = root.join("home");
+        fs::create_dir_all(home.join(".claude/friction")).unwrap();
+
+        let writable = Check::fail(
+            "friction ledger",
+            Probe::Writable(HomePath::new(".claude/friction").unwrap()),
+            "mkdir -p ~/.claude/friction",
+        );
+        let creatable = Check::fail(
+            "docket store",
+            Probe::Creatable(HomePath::new(".docket").unwrap()),
+            "fix the permissions on ~",
+        );
+        let absent = Check::warn(
+            "cache",
+            Probe::Writable(HomePath::new(".cache/absent").unwrap()),
+            "mkdir -p ~/.cache/absent",
+        );
+        let unrendered = Check::fail(
+            "go env file",
+            Probe::File(HomePath::new(".config/go/env").unwrap()),
+            "run dotfiles-activate",
+        );
+
+        let path = OsString::new();
+
+        assert_eq!(
+            writable.run("claude-code", &home, &path).outcome,
+            Outcome::Pass
+        );
+        assert_eq!(creatable.run("docket", &home, &path).outcome, Outcome::Pass);
+
+        let report = absent.run("claude-code", &home, &path);
+        assert_eq!(report.outcome, Outcome::Warn);
+        assert_eq!(
+            report.remediation.as_deref(),
+            Some("mkdir -p ~/.cache/absent")
+        );
+
+        assert_eq!(unrendered.run("go", &home, &path).outcome, Outcome::Fail);
+        fs::create_dir_all(home.join(".config/go")).unwrap();
+        fs::write(home.join(".config/go/env"), "GOTOOLCHAIN=local").unwrap();
+        assert_eq!(unrendered.run("go", &home, &path).outcome, Outcome::Pass);
+    }
+
+    #[test]
+    fn programs_are_found_on_the_given_path_and_their_output_compared() {
+        let root = scratch("programs");
+        let home = root.join("home");
+        let go = root.join("bin/go");
+        fs::write(
+            &go,
+            format!("#!/bin/sh\necho {}/.config/go/env\n", home.display()),
+        )
+        .unwrap();
+        fs::set_permissions(&go, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let path = OsString::from(root.join("bin"));
+
+        let goenv = |expected| {
+            Check::fail(
+                "go env file",
+                Probe::PrintsHomePath {
+                    args: vec!["env".to_string(), "GOENV".to_string()],
+                    expected: HomePath::new(expected).unwrap(),
+                    program: "go".to_string(),
+                },
+                "re-run activation",
+            )
+        };
+
+        assert_eq!(
+            goenv(".config/go/env").run("go", &home, &path).outcome,
+            Outcome::Pass
+        );
+        assert_eq!(
+            goenv("Library/Application Support/go/env")
+                .run("go", &home, &path)
+                .outcome,
+            Outcome::Fail
+        );
+
+        let gopls = Check::warn("gopls", Probe::OnPath("gopls".to_string()), "rebuild");
+        assert_eq!(gopls.run("neovim", &home, &path).outcome, Outcome::Warn);
+    }
+}
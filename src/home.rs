@@ -0,0 +1,133 @@
+use anyhow::{bail, Result};
+use std::fmt;
+
+/// A path inside the activating user's home directory, stored relative and
+/// normalized so two spellings of one destination compare equal. Each
+/// consumer asks for the rendering it needs instead of sharing one string.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct HomePath(String);
+
+impl HomePath {
+    /// Accepts a relative path, optionally prefixed with `~/`, `$HOME/` or
+    /// `${HOME}/`. Empty and `.` segments are dropped; absolute paths, `..`,
+    /// backslash escapes and control characters are rejected.
+    pub fn new(path: &str) -> Result<Self> {
+        let relative = ["~/", "${HOME}/", "$HOME/"]
+            .iter()
+            .find_map(|prefix| path.strip_prefix(prefix))
+            .unwrap_or(path);
+
+        if relative.starts_with('/') {
+            bail!("{path} is absolute, not inside the home directory");
+        }
+
+        if relative.contains('\\') {
+            bail!("{path} is shell-escaped; spell it as the plain path");
+        }
+
+        if relative.chars().any(char::is_control) {
+            bail!("{path:?} contains a control character");
+        }
+
+        let mut segments = vec![];
+
+        for segment in relative.split('/') {
+            match segment {
+                "" | "." => {}
+                ".." => bail!("{path} leaves the home directory"),
+                _ => segments.push(segment),
+            }
+        }
+
+        if segments.is_empty() {
+            bail!("{path} names the home directory itself");
+        }
+
+        Ok(Self(segments.join("/")))
+    }
+
+    /// A path below this one.
+    pub fn join(&self, path: &str) -> Result<Self> {
+        Self::new(&format!("{}/{path}", self.0))
+    }
+
+    pub fn relative(&self) -> &str {
+        &self.0
+    }
+
+    /// A single shell word with `${HOME}` left to expand, for Vorpal's
+    /// activation and the `dotfiles-activate` script.
+    pub fn shell(&self) -> String {
+        let escaped: String = self
+            .0
+            .chars()
+            .flat_map(|c| {
+                if c.is_ascii_alphanumeric() || "%+,-./:=@_".contains(c) {
+                    vec![c]
+                } else {
+                    vec!['\\', c]
+                }
+            })
+            .collect();
+
+        format!("${{HOME}}/{escaped}")
+    }
+
+    /// `~/`-prefixed, as the activation manifest and the Claude Code sandbox
+    /// config spell home paths.
+    pub fn tilde(&self) -> String {
+        format!("~/{}", self.0)
+    }
+}
+
+impl fmt::Display for HomePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.tilde())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HomePath;
+
+    #[test]
+    fn every_home_spelling_normalizes_to_one_path() {
+        let expected = HomePath::new(".config/go/env").unwrap();
+
+        for spelling in [
+            "~/.config/go/env",
+            "$HOME/.config/go/env",
+            "${HOME}/.config/go/env",
+            ".config//go/./env/",
+        ] {
+            assert_eq!(HomePath::new(spelling).unwrap(), expected, "{spelling}");
+        }
+    }
+
+    #[test]
+    fn spaces_are_escaped_for_the_shell_only() {
+        let path = HomePath::new("Library/Application Support/go/env").unwrap();
+
+        assert_eq!(path.shell(), "${HOME}/Library/Application\\ Support/go/env");
+        assert_eq!(path.tilde(), "~/Library/Application Support/go/env");
+    }
+
+    #[test]
+    fn paths_outside_home_are_rejected() {
+        assert!(HomePath::new("/etc/hosts").is_err());
+        assert!(HomePath::new("~/../other/.zshrc").is_err());
+        assert!(HomePath::new("~/").is_err());
+    }
+
+    #[test]
+    fn hand_escaped_paths_are_rejected() {
+        assert!(HomePath::new("Library/Application\\ Support").is_err());
+    }
+
+    #[test]
+    fn control_characters_are_rejected() {
+        // The activation manifest separates fields with tabs and lines with
+        // newlines.
+        assert!(HomePath::new(".config/bat\tconfig").is_err());
+    }
+}
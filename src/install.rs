@@ -0,0 +1,92 @@
+use crate::{home::HomePath, store::StorePath};
+use anyhow::{bail, Error, Result};
+use std::str::FromStr;
+
+/// Stands in for the activating user's home directory inside generated files,
+/// so an artifact is identical for every user who shares a store.
+pub const HOME_PLACEHOLDER: &str = "@HOME@";
+
+/// How activation puts one artifact path at its destination.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InstallMode {
+    /// A real copy of the store path, for targets a program rewrites, such
+    /// as a settings file the app saves back into. Read-only unless
+    /// `writable`; either way the next activation replaces it.
+    Copy { writable: bool },
+    /// A symlink into the store, created by Vorpal's own activation.
+    Link,
+    /// A real file copied out of the store with every placeholder expanded
+    /// for the activating user. For files read by programs that expand no
+    /// variables themselves, such as the Go env file.
+    Render,
+}
+
+impl InstallMode {
+    /// The mode as the activation manifest spells it.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            InstallMode::Copy { writable: false } => "copy",
+            InstallMode::Copy { writable: true } => "copy-writable",
+            InstallMode::Link => "link",
+            InstallMode::Render => "render",
+        }
+    }
+}
+
+impl FromStr for InstallMode {
+    type Err = Error;
+
+    fn from_str(mode: &str) -> Result<Self> {
+        match mode {
+            "copy" => Ok(InstallMode::Copy { writable: false }),
+            "copy-writable" => Ok(InstallMode::Copy { writable: true }),
+            "link" => Ok(InstallMode::Link),
+            "render" => Ok(InstallMode::Render),
+            _ => bail!("unknown install mode {mode}"),
+        }
+    }
+}
+
+/// One artifact output, or a path inside it, and the destination in `$HOME`
+/// it is installed at.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Install {
+    pub mode: InstallMode,
+    pub source: StorePath,
+    pub target: HomePath,
+}
+
+impl Install {
+    pub fn copy(source: StorePath, target: HomePath) -> Self {
+        Self {
+            mode: InstallMode::Copy { writable: false },
+            source,
+            target,
+        }
+    }
+
+    /// A copy the owning program may write to until the next activation.
+    pub fn copy_writable(source: StorePath, target: HomePath) -> Self {
+        Self {
+            mode: InstallMode::Copy { writable: true },
+            source,
+            target,
+        }
+    }
+
+    pub fn link(source: StorePath, target: HomePath) -> Self {
+        Self {
+            mode: InstallMode::Link,
+            source,
+            target,
+        }
+    }
+
+    pub fn render(source: StorePath, target: HomePath) -> Self {
+        Self {
+            mode: InstallMode::Render,
+            source,
+            target,
+        }
+    }
+}
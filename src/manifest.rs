@@ -0,0 +1,340 @@
+use crate::{
+    home::HomePath,
+    install::{InstallMode, HOME_PLACEHOLDER},
+    store::Store,
+};
+use anyhow::{bail, Context, Result};
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Where `dotfiles-activate` records the manifest of the generation it just
+/// activated, relative to `$HOME`.
+pub const STATE_MANIFEST_PATH: &str = ".local/state/dotfiles/manifest";
+
+/// One line of an activation manifest: how a store path was installed and
+/// where. The source is the resolved store path, not an artifact key.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ManifestEntry {
+    pub mode: InstallMode,
+    pub source: PathBuf,
+    pub target: HomePath,
+}
+
+/// How an installed target compares with what the manifest declares.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Status {
+    Correct,
+    Missing,
+    /// A link into the store that resolves somewhere else, usually another
+    /// generation's output.
+    DifferentDigest(PathBuf),
+    /// A link where the declared one belongs that points outside the store.
+    OutsideStore(PathBuf),
+    /// A real file or directory where a link belongs.
+    RegularFile,
+    /// A copy or rendered file whose content no longer matches the store,
+    /// or a link where a real file belongs.
+    Modified,
+    /// A copy or rendered file whose store source is gone, usually because
+    /// its generation was garbage-collected, so there is nothing to compare.
+    SourceMissing,
+}
+
+impl Status {
+    pub fn is_drift(&self) -> bool {
+        *self != Status::Correct
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Correct => f.write_str("correct"),
+            Status::Missing => f.write_str("missing"),
+            Status::DifferentDigest(actual) => {
+                write!(f, "different digest: {}", actual.display())
+            }
+            Status::OutsideStore(actual) => {
+                write!(f, "outside the store: {}", actual.display())
+            }
+            Status::RegularFile => f.write_str("regular file"),
+            Status::Modified => f.write_str("modified"),
+            Status::SourceMissing => f.write_str("source missing"),
+        }
+    }
+}
+
+/// Parses the tab-separated `mode`, `source`, `target` lines the activation
+/// script writes. Blank lines are skipped.
+pub fn parse(content: &str) -> Result<Vec<ManifestEntry>> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(index, line)| {
+            let fields: Vec<&str> = line.split('\t').collect();
+
+            let [mode, source, target] = fields[..] else {
+                bail!("manifest line {}: expected three fields", index + 1);
+            };
+
+            Ok(ManifestEntry {
+                mode: mode.parse()?,
+                source: PathBuf::from(source),
+                target: HomePath::new(target)?,
+            })
+        })
+        .collect()
+}
+
+/// Whether two paths hold the same tree: equal files, equal link targets,
+/// and directories with the same entries, compared recursively.
+fn same_tree(a: &Path, b: &Path) -> io::Result<bool> {
+    let (a_meta, b_meta) = (fs::symlink_metadata(a)?, fs::symlink_metadata(b)?);
+
+    if a_meta.is_symlink() || b_meta.is_symlink() {
+        return Ok(a_meta.is_symlink()
+            && b_meta.is_symlink()
+            && fs::read_link(a)? == fs::read_link(b)?);
+    }
+
+    if a_meta.is_dir() != b_meta.is_dir() {
+        return Ok(false);
+    }
+
+    if !a_meta.is_dir() {
+        return Ok(fs::read(a)? == fs::read(b)?);
+    }
+
+    let names = |path: &Path| -> io::Result<Vec<_>> {
+        let mut names = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect::<io::Result<Vec<_>>>()?;
+        names.sort();
+        Ok(names)
+    };
+
+    let a_names = names(a)?;
+
+    if a_names != names(b)? {
+        return Ok(false);
+    }
+
+    for name in a_names {
+        if !same_tree(&a.join(&name), &b.join(&name))? {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+impl ManifestEntry {
+    /// Compares the target under `home` with the entry, reading but never
+    /// changing either side. A writable copy only has to exist.
+    pub fn status(&self, home: &Path, store: &Store) -> Result<Status> {
+        let path = home.join(self.target.relative());
+
+        let metadata = match fs::symlink_metadata(&path) {
+            Ok(metadata) => metadata,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Status::Missing),
+            Err(error) => return Err(error).with_context(|| path.display().to_string()),
+        };
+
+        let compared = |unchanged| {
+            if unchanged {
+                Status::Correct
+            } else {
+                Status::Modified
+            }
+        };
+
+        Ok(match self.mode {
+            InstallMode::Link if !metadata.is_symlink() => Status::RegularFile,
+            InstallMode::Link => {
+                let actual = fs::read_link(&path)?;
+
+                if actual == self.source {
+                    Status::Correct
+                } else if store.contains(&actual) {
+                    Status::DifferentDigest(actual)
+                } else {
+                    Status::OutsideStore(actual)
+                }
+            }
+            _ if metadata.is_symlink() => Status::Modified,
+            InstallMode::Copy { writable: true } => Status::Correct,
+            InstallMode::Copy { writable: false } | InstallMode::Render
+                if !self
+                    .source
+                    .try_exists()
+                    .with_context(|| self.source.display().to_string())? =>
+            {
+                Status::SourceMissing
+            }
+            InstallMode::Copy { writable: false } => compared(same_tree(&self.source, &path)?),
+            InstallMode::Render => {
+                let source = fs::read_to_string(&self.source)
+                    .with_context(|| self.source.display().to_string())?;
+                let expected = source.replace(HOME_PLACEHOLDER, &home.to_string_lossy());
+
+                compared(fs::read(&path)? == expected.as_bytes())
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, ManifestEntry, Status};
+    use crate::{home::HomePath, install::InstallMode, store::Store};
+    use std::{fs, os::unix::fs::symlink, path::PathBuf};
+
+    /// A scratch directory with a `store` and a `home`, removed and
+    /// recreated per test and unique to the test process.
+    fn scratch(name: &str) -> (PathBuf, PathBuf) {
+        let root =
+            std::env::temp_dir().join(format!("dotfiles-manifest-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+
+        let (store, home) = (root.join("store"), root.join("home"));
+        fs::create_dir_all(&store).unwrap();
+        fs::create_dir_all(home.join(".config")).unwrap();
+
+        (store, home)
+    }
+
+    fn entry(mode: InstallMode, source: PathBuf, target: &str) -> ManifestEntry {
+        ManifestEntry {
+            mode,
+            source,
+            target: HomePath::new(target).unwrap(),
+        }
+    }
+
+    #[test]
+    fn manifests_parse_as_the_activation_script_writes_them() {
+        let entries = parse(
+            "link\t/var/lib/vorpal/store/a\t.config/bat/config\n\
+             \n\
+             copy-writable\t/var/lib/vorpal/store/b\t.claude/settings.json\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            entries,
+            vec![
+                entry(
+                    InstallMode::Link,
+                    "/var/lib/vorpal/store/a".into(),
+                    ".config/bat/config"
+                ),
+                entry(
+                    InstallMode::Copy { writable: true },
+                    "/var/lib/vorpal/store/b".into(),
+                    ".claude/settings.json"
+                ),
+            ]
+        );
+        assert!(parse("link\t/a").is_err());
+        assert!(parse("hardlink\t/a\t.config/bat/config").is_err());
+    }
+
+    #[test]
+    fn links_are_checked_against_their_store_path() {
+        let (store, home) = scratch("links");
+        fs::write(store.join("a"), "a").unwrap();
+        fs::write(store.join("b"), "b").unwrap();
+
+        let declared = |target| entry(InstallMode::Link, store.join("a"), target);
+        let status = |target| declared(target).status(&home, &Store::new(&store));
+
+        symlink(store.join("a"), home.join(".config/correct")).unwrap();
+        symlink(store.join("b"), home.join(".config/other")).unwrap();
+        symlink("/elsewhere/a", home.join(".config/foreign")).unwrap();
+        fs::write(home.join(".config/edited"), "a").unwrap();
+
+        assert_eq!(status(".config/correct").unwrap(), Status::Correct);
+        assert_eq!(
+            status(".config/other").unwrap(),
+            Status::DifferentDigest(store.join("b"))
+        );
+        assert_eq!(
+            status(".config/foreign").unwrap(),
+            Status::OutsideStore("/elsewhere/a".into())
+        );
+        assert_eq!(status(".config/edited").unwrap(), Status::RegularFile);
+        assert_eq!(status(".config/absent").unwrap(), Status::Missing);
+    }
+
+    #[test]
+    fn copies_and_renders_are_checked_by_content() {
+        let (store, home) = scratch("copies");
+        let vorpal = Store::new(&store);
+        fs::create_dir_all(store.join("tree/nested")).unwrap();
+        fs::write(store.join("tree/nested/file"), "x").unwrap();
+        fs::write(store.join("env"), "GOPATH=@HOME@/go\n").unwrap();
+
+        fs::create_dir_all(home.join(".config/tree/nested")).unwrap();
+        fs::write(home.join(".config/tree/nested/file"), "x").unwrap();
+        fs::write(
+            home.join(".config/env"),
+            format!("GOPATH={}/go\n", home.display()),
+        )
+        .unwrap();
+        fs::write(home.join(".config/settings.json"), "edited").unwrap();
+
+        let copy = entry(
+            InstallMode::Copy { writable: false },
+            store.join("tree"),
+            ".config/tree",
+        );
+        let render = entry(InstallMode::Render, store.join("env"), ".config/env");
+        let writable = entry(
+            InstallMode::Copy { writable: true },
+            store.join("settings.json"),
+            ".config/settings.json",
+        );
+
+        assert_eq!(copy.status(&home, &vorpal).unwrap(), Status::Correct);
+        assert_eq!(render.status(&home, &vorpal).unwrap(), Status::Correct);
+        assert_eq!(writable.status(&home, &vorpal).unwrap(), Status::Correct);
+
+        fs::write(home.join(".config/tree/nested/file"), "y").unwrap();
+        fs::write(home.join(".config/env"), "GOPATH=/elsewhere\n").unwrap();
+
+        assert_eq!(copy.status(&home, &vorpal).unwrap(), Status::Modified);
+        assert_eq!(render.status(&home, &vorpal).unwrap(), Status::Modified);
+    }
+
+    #[test]
+    fn collected_sources_are_reported_not_fatal() {
+        let (store, home) = scratch("collected");
+        let vorpal = Store::new(&store);
+        fs::create_dir_all(home.join(".config/tree")).unwrap();
+        fs::write(home.join(".config/env"), "GOPATH=/go\n").unwrap();
+        fs::write(home.join(".config/settings.json"), "{}").unwrap();
+
+        let copy = entry(
+            InstallMode::Copy { writable: false },
+            store.join("tree"),
+            ".config/tree",
+        );
+        let render = entry(InstallMode::Render, store.join("env"), ".config/env");
+        let writable = entry(
+            InstallMode::Copy { writable: true },
+            store.join("settings.json"),
+            ".config/settings.json",
+        );
+
+        assert_eq!(copy.status(&home, &vorpal).unwrap(), Status::SourceMissing);
+        assert_eq!(
+            render.status(&home, &vorpal).unwrap(),
+            Status::SourceMissing
+        );
+        assert_eq!(writable.status(&home, &vorpal).unwrap(), Status::Correct);
+        assert!(Status::SourceMissing.is_drift());
+    }
+}
@@ -0,0 +1,232 @@
+use crate::{
+    user::{utilities::Profile, Component},
+    SYSTEMS,
+};
+use anyhow::{bail, Context, Result};
+use serde::{
+    de::{value, IntoDeserializer},
+    Deserialize,
+};
+use std::env;
+use vorpal_sdk::api::artifact::ArtifactSystem;
+
+/// Top-level artifacts to build, comma-separated: `dev`, `user`.
+pub const ARTIFACTS_VAR: &str = "DOTFILES_ARTIFACTS";
+
+/// `false` skips the catalog tools and builds only the components.
+pub const CATALOG_VAR: &str = "DOTFILES_CATALOG";
+
+/// Components to build instead of those in `dotfiles.toml`, comma-separated,
+/// spelled as there.
+pub const COMPONENTS_VAR: &str = "DOTFILES_COMPONENTS";
+
+/// The catalog profile, overriding `dotfiles.toml`.
+pub const PROFILE_VAR: &str = "DOTFILES_PROFILE";
+
+/// Systems to declare the artifacts for, comma-separated, e.g.
+/// `aarch64-darwin,x8664-linux`.
+pub const SYSTEMS_VAR: &str = "DOTFILES_SYSTEMS";
+
+/// A top-level artifact `vorpal` can be asked for.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Build {
+    Dev,
+    User,
+}
+
+/// What one `vorpal` evaluation builds. The config binary's arguments belong
+/// to the SDK's context, so the selection comes from the environment; every
+/// variable left unset or empty keeps today's full build.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Selection {
+    pub builds: Vec<Build>,
+    pub catalog: bool,
+    pub components: Option<Vec<Component>>,
+    pub profile: Option<Profile>,
+    pub systems: Vec<ArtifactSystem>,
+}
+
+/// Deserializes one value through the type's own serde spelling, so the
+/// environment accepts exactly what `dotfiles.toml` does.
+fn parse_value<'de, T: Deserialize<'de>>(var: &str, value: &'de str) -> Result<T> {
+    T::deserialize(IntoDeserializer::<value::Error>::into_deserializer(value))
+        .with_context(|| format!("{var}: {value} is not recognised"))
+}
+
+fn parse_list<'de, T: Deserialize<'de>>(var: &str, value: &'de str) -> Result<Vec<T>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| parse_value(var, item))
+        .collect()
+}
+
+/// Vorpal's spelling of a system on the command line: `aarch64-darwin`.
+fn system_name(system: ArtifactSystem) -> String {
+    system.as_str_name().to_lowercase().replace('_', "-")
+}
+
+fn parse_systems(value: &str) -> Result<Vec<ArtifactSystem>> {
+    let mut systems = vec![];
+
+    for name in value.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        match SYSTEMS.iter().find(|system| system_name(**system) == name) {
+            Some(system) => systems.push(*system),
+            None => bail!(
+                "{SYSTEMS_VAR}: {name} is not one of {}",
+                SYSTEMS.map(system_name).join(", ")
+            ),
+        }
+    }
+
+    Ok(systems)
+}
+
+impl Selection {
+    pub fn from_env() -> Result<Self> {
+        Self::parse(|var| env::var(var).ok())
+    }
+
+    /// Reads the selection through `var`, which returns a variable's value
+    /// if it is set.
+    pub fn parse(var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let var = |name: &str| var(name).filter(|value| !value.trim().is_empty());
+
+        let builds = match var(ARTIFACTS_VAR) {
+            Some(value) => parse_list(ARTIFACTS_VAR, &value)?,
+            None => vec![Build::Dev, Build::User],
+        };
+
+        let catalog = match var(CATALOG_VAR).as_deref().map(str::trim) {
+            None | Some("true") => true,
+            Some("false") => false,
+            Some(value) => bail!("{CATALOG_VAR}: expected true or false, not {value}"),
+        };
+
+        let components = match var(COMPONENTS_VAR) {
+            Some(value) => Some(parse_list(COMPONENTS_VAR, &value)?),
+            None => None,
+        };
+
+        let profile = match var(PROFILE_VAR) {
+            Some(value) => Some(parse_value(PROFILE_VAR, value.trim())?),
+            None => None,
+        };
+
+        let systems = match var(SYSTEMS_VAR) {
+            Some(value) => parse_systems(&value)?,
+            None => SYSTEMS.to_vec(),
+        };
+
+        Ok(Self {
+            builds,
+            catalog,
+            components,
+            profile,
+            systems,
+        })
+    }
+
+    pub fn builds(&self, build: Build) -> bool {
+        self.builds.contains(&build)
+    }
+
+    /// The name the user environment is built under. A build narrowed by
+    /// the catalog, components or profile is `user-partial`, so it is never
+    /// mistaken for `user`; its activation neither collects stale links nor
+    /// records its manifest. The justfile's `user` variable makes the same
+    /// choice.
+    pub fn user_name(&self) -> &'static str {
+        if self.catalog && self.components.is_none() && self.profile.is_none() {
+            "user"
+        } else {
+            "user-partial"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Build, Selection};
+    use crate::{
+        user::{utilities::Profile, Component},
+        SYSTEMS,
+    };
+    use std::collections::HashMap;
+    use vorpal_sdk::api::artifact::ArtifactSystem::{Aarch64Darwin, X8664Linux};
+
+    fn parse(vars: &[(&str, &str)]) -> anyhow::Result<Selection> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+
+        Selection::parse(|key| vars.get(key).cloned())
+    }
+
+    #[test]
+    fn nothing_set_builds_everything() {
+        assert_eq!(
+            parse(&[("DOTFILES_COMPONENTS", " ")]).unwrap(),
+            Selection {
+                builds: vec![Build::Dev, Build::User],
+                catalog: true,
+                components: None,
+                profile: None,
+                systems: SYSTEMS.to_vec(),
+            }
+        );
+    }
+
+    #[test]
+    fn one_component_on_one_system() {
+        let selection = parse(&[
+            ("DOTFILES_ARTIFACTS", "user"),
+            ("DOTFILES_CATALOG", "false"),
+            ("DOTFILES_COMPONENTS", "ghostty, claude-code"),
+            ("DOTFILES_PROFILE", "minimal"),
+            ("DOTFILES_SYSTEMS", "aarch64-darwin,x8664-linux"),
+        ])
+        .unwrap();
+
+        assert!(!selection.builds(Build::Dev));
+        assert_eq!(selection.user_name(), "user-partial");
+        assert!(!selection.catalog);
+        assert_eq!(
+            selection.components,
+            Some(vec![Component::Ghostty, Component::ClaudeCode])
+        );
+        assert_eq!(selection.profile, Some(Profile::Minimal));
+        assert_eq!(selection.systems, vec![Aarch64Darwin, X8664Linux]);
+    }
+
+    #[test]
+    fn only_a_narrowed_build_is_partial() {
+        assert_eq!(parse(&[]).unwrap().user_name(), "user");
+        assert_eq!(
+            parse(&[("DOTFILES_SYSTEMS", "aarch64-darwin")])
+                .unwrap()
+                .user_name(),
+            "user"
+        );
+
+        for narrowed in [
+            ("DOTFILES_CATALOG", "false"),
+            ("DOTFILES_COMPONENTS", "ghostty"),
+            ("DOTFILES_PROFILE", "minimal"),
+        ] {
+            assert_eq!(parse(&[narrowed]).unwrap().user_name(), "user-partial");
+        }
+    }
+
+    #[test]
+    fn unknown_names_are_rejected() {
+        assert!(parse(&[("DOTFILES_ARTIFACTS", "docs")]).is_err());
+        assert!(parse(&[("DOTFILES_CATALOG", "no")]).is_err());
+        assert!(parse(&[("DOTFILES_COMPONENTS", "ghosty")]).is_err());
+        assert!(parse(&[("DOTFILES_PROFILE", "laptop")]).is_err());
+        assert!(parse(&[("DOTFILES_SYSTEMS", "riscv64-linux")]).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+use std::{env, fs, path::PathBuf};
+
+/// Set to accept every rendering that differs from its snapshot: the file is
+/// rewritten and the diff printed instead of failing the test.
+pub const UPDATE_VAR: &str = "DOTFILES_UPDATE_SNAPSHOTS";
+
+/// Unchanged lines kept around each change in a diff.
+const CONTEXT: usize = 2;
+
+/// Follows a final line with no newline after it, as in `diff -u`.
+const NO_NEWLINE: &str = "\\ No newline at end of file";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Line<'a> {
+    Added(&'a str),
+    Removed(&'a str),
+    Same(&'a str),
+}
+
+/// The lines of `old` and `new` in order, each marked kept, removed or
+/// added, from their longest common subsequence. Lines keep their newline,
+/// so a final line missing one differs from the same line with it.
+fn lines<'a>(old: &'a str, new: &'a str) -> Vec<Line<'a>> {
+    let (old, new): (Vec<&str>, Vec<&str>) = (
+        old.split_inclusive('\n').collect(),
+        new.split_inclusive('\n').collect(),
+    );
+
+    // common[i][j]: the longest common subsequence of old[i..] and new[j..].
+    let mut common = vec![vec![0; new.len() + 1]; old.len() + 1];
+
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = vec![];
+
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(Line::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || common[i][j + 1] > common[i + 1][j]) {
+            lines.push(Line::Added(new[j]));
+            j += 1;
+        } else {
+            lines.push(Line::Removed(old[i]));
+            i += 1;
+        }
+    }
+
+    lines
+}
+
+/// A line diff for review: `-` for the snapshot's lines, `+` for the new
+/// rendering's, and a few unchanged lines around each change.
+pub fn diff(old: &str, new: &str) -> String {
+    let lines = lines(old, new);
+
+    let near_change = |index: usize| {
+        let start = index.saturating_sub(CONTEXT);
+        let end = (index + CONTEXT + 1).min(lines.len());
+
+        lines[start..end]
+            .iter()
+            .any(|line| !matches!(line, Line::Same(_)))
+    };
+
+    let mut output = vec![];
+    let mut skipped = false;
+
+    for (index, line) in lines.iter().enumerate() {
+        if !near_change(index) {
+            skipped = true;
+            continue;
+        }
+
+        if skipped {
+            output.push("  ...".to_string());
+            skipped = false;
+        }
+
+        let (marker, text) = match line {
+            Line::Added(text) => ('+', text),
+            Line::Removed(text) => ('-', text),
+            Line::Same(text) => (' ', text),
+        };
+
+        output.push(match text.strip_suffix('\n') {
+            Some(text) => format!("{marker} {text}"),
+            None => format!("{marker} {text}\n{NO_NEWLINE}"),
+        });
+    }
+
+    output.join("\n")
+}
+
+/// Compares a rendering with `tests/snapshots/{name}`. With [`UPDATE_VAR`]
+/// set, a missing or different snapshot is written instead.
+pub fn assert_snapshot(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(name);
+
+    let expected = fs::read_to_string(&path).ok();
+
+    if expected.as_deref() == Some(actual) {
+        return;
+    }
+
+    let diff = diff(expected.as_deref().unwrap_or_default(), actual);
+
+    if env::var_os(UPDATE_VAR).is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        eprintln!("updated {}:\n{diff}\n", path.display());
+        return;
+    }
+
+    match expected {
+        Some(_) => panic!(
+            "{} differs from the rendering; run `just update-snapshots` to accept it:\n{diff}",
+            path.display()
+        ),
+        None => panic!(
+            "{} is missing; run `just update-snapshots` to write it",
+            path.display()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff;
+
+    #[test]
+    fn diffs_mark_changes_and_elide_distant_unchanged_lines() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\n";
+        let new = "a\nb\nc\nd\ne\nF\ng\nh\ni\n";
+
+        assert_eq!(diff(old, new), "  ...\n  d\n  e\n- f\n+ F\n  g\n  h\n+ i");
+    }
+
+    #[test]
+    fn a_missing_final_newline_is_shown() {
+        assert_eq!(
+            diff("a\nb\n", "a\nb"),
+            "  a\n- b\n+ b\n\\ No newline at end of file"
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+use vorpal_sdk::artifact::get_env_key;
+
+/// Where Vorpal keeps every artifact unless told otherwise. Anything linked
+/// from outside the store was not installed by an activation.
+pub const STORE_ROOT: &str = "/var/lib/vorpal/store";
+
+/// Overrides [`STORE_ROOT`], for a Vorpal configured with another store or a
+/// test running against a scratch directory.
+pub const STORE_ROOT_VAR: &str = "DOTFILES_STORE_ROOT";
+
+/// The namespace artifacts are built in when a config names none.
+pub const DEFAULT_NAMESPACE: &str = "library";
+
+/// A Vorpal store on disk.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Store {
+    root: PathBuf,
+}
+
+/// One artifact's output, or a file or directory inside it, named by digest
+/// rather than by where some store keeps it.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct StorePath {
+    pub digest: String,
+    pub namespace: String,
+    pub path: Option<String>,
+}
+
+impl Default for Store {
+    fn default() -> Self {
+        Self::new(STORE_ROOT)
+    }
+}
+
+impl Store {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The store named by [`STORE_ROOT_VAR`], or the default one if it is
+    /// unset or empty.
+    pub fn from_env() -> Self {
+        match env::var(STORE_ROOT_VAR) {
+            Ok(root) if !root.trim().is_empty() => Self::new(root),
+            _ => Self::default(),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        path.starts_with(&self.root)
+    }
+
+    /// Where this store keeps `path`:
+    /// `{root}/artifact/output/{namespace}/{digest}[/{path}]`.
+    pub fn resolve(&self, path: &StorePath) -> PathBuf {
+        let output = self
+            .root
+            .join("artifact/output")
+            .join(&path.namespace)
+            .join(&path.digest);
+
+        match &path.path {
+            Some(inner) => output.join(inner),
+            None => output,
+        }
+    }
+}
+
+impl StorePath {
+    /// The whole output of the artifact built as `digest`.
+    pub fn output(digest: &str) -> Self {
+        Self {
+            digest: digest.to_string(),
+            namespace: DEFAULT_NAMESPACE.to_string(),
+            path: None,
+        }
+    }
+
+    pub fn with_namespace(mut self, namespace: &str) -> Self {
+        self.namespace = namespace.to_string();
+        self
+    }
+
+    /// A file or directory inside this path.
+    pub fn join(&self, path: &str) -> Self {
+        let path = path.trim_matches('/');
+
+        Self {
+            path: Some(match &self.path {
+                Some(parent) => format!("{parent}/{path}"),
+                None => path.to_string(),
+            }),
+            ..self.clone()
+        }
+    }
+
+    /// The path spelled with the artifact's environment key, so Vorpal
+    /// resolves it wherever its store lives.
+    pub fn env_key(&self) -> String {
+        match &self.path {
+            Some(path) => format!("{}/{path}", get_env_key(&self.digest)),
+            None => get_env_key(&self.digest),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Store, StorePath};
+    use std::{fs, path::Path};
+
+    #[test]
+    fn outputs_resolve_under_the_store_namespace() {
+        let store = Store::default();
+        let output = StorePath::output("8814b4d3fa73");
+
+        assert_eq!(
+            store.resolve(&output),
+            Path::new("/var/lib/vorpal/store/artifact/output/library/8814b4d3fa73")
+        );
+        assert_eq!(
+            store.resolve(&output.with_namespace("user").join("bin/").join("/bat")),
+            Path::new("/var/lib/vorpal/store/artifact/output/user/8814b4d3fa73/bin/bat")
+        );
+    }
+
+    #[test]
+    fn files_resolve_inside_a_scratch_store() {
+        let root = std::env::temp_dir().join(format!("dotfiles-store-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+
+        let store = Store::new(&root);
+        let config = StorePath::output("abc123").join("user-bat-config");
+        let resolved = store.resolve(&config);
+
+        fs::create_dir_all(resolved.parent().unwrap()).unwrap();
+        fs::write(&resolved, "--theme=tokyonight").unwrap();
+
+        assert_eq!(
+            resolved,
+            root.join("artifact/output/library/abc123/user-bat-config")
+        );
+        assert!(store.contains(&resolved));
+        assert!(!Store::default().contains(&resolved));
+        assert_eq!(
+            fs::read_to_string(store.resolve(&config)).unwrap(),
+            "--theme=tokyonight"
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn only_paths_under_the_root_are_in_the_store() {
+        let store = Store::new("/scratch/store");
+
+        assert!(store.contains(Path::new("/scratch/store/artifact/output/abc123/bin")));
+        assert!(!store.contains(Path::new("/scratch/store-old/abc123")));
+        assert!(!Store::default().contains(Path::new("/scratch/store/abc123")));
+    }
+
+    #[test]
+    fn env_keys_name_the_digest_and_keep_the_inner_path() {
+        assert_eq!(
+            StorePath::output("abc123").env_key(),
+            "$VORPAL_ARTIFACT_abc123"
+        );
+        assert_eq!(
+            StorePath::output("abc123")
+                .join("bin")
+                .join("dotfiles-activate")
+                .env_key(),
+            "$VORPAL_ARTIFACT_abc123/bin/dotfiles-activate"
+        );
+    }
+}
@@ -1,104 +1,514 @@
-use crate::user::{
-    bat::Bat, claude_code::ClaudeCode, docket::Docket, ghostty::Ghostty, go::Go, k9s::K9s,
-    neovim::Neovim,
+use crate::{
+    context::BuildContext,
+    environment::{Environment, PathEntry},
+    health::Check,
+    home::HomePath,
+    install::{Install, InstallMode},
+    store::{Store, StorePath},
+    user::{
+        activation::Activation, config::Config, overlay::Overlay, shell_env::ShellEnv,
+        utilities::Profile,
+    },
+    DARWIN_SYSTEMS, SYSTEMS,
 };
 use anyhow::{bail, Result};
+use serde::Deserialize;
 use std::collections::BTreeSet;
-use vorpal_sdk::{api::artifact::ArtifactSystem, artifact, context::ConfigContext};
-
+use vorpal_sdk::api::artifact::ArtifactSystem;
+
+#[cfg(feature = "bat")]
+use crate::user::bat::Bat;
+#[cfg(feature = "claude-code")]
+use crate::user::claude_code::ClaudeCode;
+#[cfg(feature = "docket")]
+use crate::user::docket::Docket;
+#[cfg(feature = "ghostty")]
+use crate::user::ghostty::Ghostty;
+#[cfg(feature = "go")]
+use crate::user::go::Go;
+#[cfg(feature = "go-tools")]
+use crate::user::go_tools::GoTools;
+#[cfg(feature = "k9s")]
+use crate::user::k9s::K9s;
+#[cfg(feature = "neovim")]
+use crate::user::neovim::Neovim;
+
+mod activation;
+#[cfg(feature = "bat")]
 mod bat;
+#[cfg(feature = "claude-code")]
 mod claude_code;
+pub mod config;
+#[cfg(feature = "docket")]
 mod docket;
+#[cfg(feature = "ghostty")]
 mod ghostty;
+#[cfg(feature = "go")]
 mod go;
+#[cfg(feature = "go-tools")]
+mod go_tools;
+#[cfg(feature = "k9s")]
 mod k9s;
+#[cfg(feature = "neovim")]
 mod neovim;
-mod utilities;
+pub mod overlay;
+mod shell_env;
+pub mod utilities;
 
 pub struct UserEnvironment {
+    catalog: bool,
+    components: Option<Vec<Component>>,
+    config: Option<Config>,
     name: String,
+    overlay: Option<Overlay>,
+    profile: Option<Profile>,
+    store: Store,
     systems: Vec<ArtifactSystem>,
 }
 
+/// A configured program built alongside the catalog tools. Each declares the
+/// systems it makes sense on; `UserEnvironment` leaves it out everywhere else.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Component {
+    Bat,
+    ClaudeCode,
+    Docket,
+    Ghostty,
+    Go,
+    GoTools,
+    K9s,
+    Neovim,
+}
+
+pub const COMPONENTS: &[Component] = &[
+    Component::Bat,
+    Component::ClaudeCode,
+    Component::Docket,
+    Component::Ghostty,
+    Component::Go,
+    Component::GoTools,
+    Component::K9s,
+    Component::Neovim,
+];
+
+impl Component {
+    pub fn name(self) -> &'static str {
+        match self {
+            Component::Bat => "bat",
+            Component::ClaudeCode => "claude-code",
+            Component::Docket => "docket",
+            Component::Ghostty => "ghostty",
+            Component::Go => "go",
+            Component::GoTools => "go-tools",
+            Component::K9s => "k9s",
+            Component::Neovim => "neovim",
+        }
+    }
+
+    pub fn systems(self) -> &'static [ArtifactSystem] {
+        match self {
+            // Ghostty's config lives under `~/Library/Application Support`,
+            // and the Linux build is not one anyone here runs.
+            Component::Ghostty => &DARWIN_SYSTEMS,
+            _ => &SYSTEMS,
+        }
+    }
+
+    pub fn supports(self, system: ArtifactSystem) -> bool {
+        self.systems().contains(&system)
+    }
+
+    /// Whether this binary was compiled with the component's cargo feature.
+    pub fn compiled(self) -> bool {
+        match self {
+            Component::Bat => cfg!(feature = "bat"),
+            Component::ClaudeCode => cfg!(feature = "claude-code"),
+            Component::Docket => cfg!(feature = "docket"),
+            Component::Ghostty => cfg!(feature = "ghostty"),
+            Component::Go => cfg!(feature = "go"),
+            Component::GoTools => cfg!(feature = "go-tools"),
+            Component::K9s => cfg!(feature = "k9s"),
+            Component::Neovim => cfg!(feature = "neovim"),
+        }
+    }
+
+    /// What `dotfiles-doctor` checks once the component is activated on
+    /// `system`. Components with nothing outside the store to go wrong have
+    /// no checks.
+    #[cfg_attr(not(feature = "go"), allow(unused_variables))]
+    pub fn checks(self, system: ArtifactSystem) -> Result<Vec<Check>> {
+        match self {
+            #[cfg(feature = "claude-code")]
+            Component::ClaudeCode => ClaudeCode::checks(),
+            #[cfg(feature = "docket")]
+            Component::Docket => Docket::checks(),
+            #[cfg(feature = "go")]
+            Component::Go => Go::checks(system),
+            #[cfg(feature = "neovim")]
+            Component::Neovim => Ok(Neovim::checks()),
+            _ => Ok(vec![]),
+        }
+    }
+}
+
+/// The checks of every component the configuration builds on `system`, in
+/// component order.
+pub fn health_checks(config: &Config, system: ArtifactSystem) -> Result<Vec<(Component, Check)>> {
+    let mut checks = vec![];
+
+    for &component in COMPONENTS {
+        if config.has_component(component) && component.compiled() && component.supports(system) {
+            for check in component.checks(system)? {
+                checks.push((component, check));
+            }
+        }
+    }
+
+    Ok(checks)
+}
+
+/// Which catalog tools and components build on which system, one row each
+/// with an `x` per supported system, as a Markdown table.
+pub fn availability_matrix() -> String {
+    let mut rows: Vec<(&str, &[ArtifactSystem])> = utilities::CATALOG
+        .iter()
+        .map(|entry| (entry.name, entry.systems))
+        .collect();
+
+    rows.extend(
+        COMPONENTS
+            .iter()
+            .map(|component| (component.name(), component.systems())),
+    );
+
+    let header = SYSTEMS
+        .iter()
+        .map(|system| system.as_str_name())
+        .collect::<Vec<_>>();
+
+    let mut matrix = format!(
+        "| | {} |\n|---|{}\n",
+        header.join(" | "),
+        "---|".repeat(header.len())
+    );
+
+    for (name, systems) in rows {
+        let cells = SYSTEMS
+            .iter()
+            .map(|system| if systems.contains(system) { "x" } else { " " })
+            .collect::<Vec<_>>();
+
+        matrix.push_str(&format!("| {name} | {} |\n", cells.join(" | ")));
+    }
+
+    matrix
+}
+
 /// Install destinations claimed by more than one artifact, sorted and listed
-/// once each. Activation aborts when two symlinks target the same path, so the
-/// build stops here instead, naming the collision.
-fn duplicate_symlink_targets(symlinks: &[(String, String)]) -> Vec<String> {
+/// once each. Activation aborts when two symlinks target the same path, and a
+/// rendered file would silently overwrite a link, so the build stops here
+/// instead, naming the collision.
+fn duplicate_symlink_targets(installs: &[Install]) -> Vec<String> {
     let mut seen = BTreeSet::new();
     let mut duplicates = BTreeSet::new();
 
-    for (_, target) in symlinks {
-        if !seen.insert(target) {
-            duplicates.insert(target.clone());
+    for install in installs {
+        if !seen.insert(&install.target) {
+            duplicates.insert(&install.target);
         }
     }
 
-    duplicates.into_iter().collect()
+    duplicates.into_iter().map(HomePath::tilde).collect()
+}
+
+/// Component selection, used only by the components' own blocks in `build`
+/// and so unused when none is compiled in.
+#[cfg_attr(
+    not(any(
+        feature = "bat",
+        feature = "claude-code",
+        feature = "docket",
+        feature = "ghostty",
+        feature = "go",
+        feature = "go-tools",
+        feature = "k9s",
+        feature = "neovim"
+    )),
+    allow(dead_code)
+)]
+impl UserEnvironment {
+    /// Whether the environment builds the component on `system`: chosen by
+    /// `with_components`, or else by `config`, and supported there.
+    fn builds(&self, config: &Config, component: Component, system: ArtifactSystem) -> bool {
+        let selected = match &self.components {
+            Some(components) => components.contains(&component),
+            None => config.has_component(component),
+        };
+
+        selected && component.supports(system)
+    }
+
+    /// The environment's systems the component also supports.
+    fn component_systems(&self, component: Component) -> Vec<ArtifactSystem> {
+        self.systems
+            .iter()
+            .copied()
+            .filter(|system| component.systems().contains(system))
+            .collect()
+    }
 }
 
 impl UserEnvironment {
     pub fn new(name: &str, systems: Vec<ArtifactSystem>) -> Self {
         UserEnvironment {
+            catalog: true,
+            components: None,
+            config: None,
             name: name.to_string(),
+            overlay: None,
+            profile: None,
+            store: Store::default(),
             systems,
         }
     }
 
-    pub async fn build(self, context: &mut ConfigContext) -> Result<String> {
-        let binaries = utilities::build(context).await?;
+    /// Whether to build the catalog tools at all. Off, only the components
+    /// build, for iterating on one of them.
+    pub fn with_catalog(mut self, catalog: bool) -> Self {
+        self.catalog = catalog;
+        self
+    }
 
-        let bat = Bat::new(&self.name, self.systems.clone())
-            .with_theme("tokyonight")
-            .build(context)
-            .await?;
+    /// Builds only these components instead of the configuration's.
+    pub fn with_components(mut self, components: Vec<Component>) -> Self {
+        self.components = Some(components);
+        self
+    }
 
-        let claude_code = ClaudeCode::new(&self.name, self.systems.clone())
-            .build(context)
-            .await?;
+    /// Builds from this configuration instead of the checked-in
+    /// `dotfiles.toml`.
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = Some(config);
+        self
+    }
 
-        let docket = Docket::new(&self.name, self.systems.clone())
-            .build(context)
-            .await?;
+    /// Applies this overlay instead of detecting one for the build machine.
+    pub fn with_overlay(mut self, overlay: Overlay) -> Self {
+        self.overlay = Some(overlay);
+        self
+    }
 
-        let ghostty = Ghostty::new(&self.name, self.systems.clone())
-            .build(context)
-            .await?;
+    /// Overrides the configuration's profile.
+    pub fn with_profile(mut self, profile: Profile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
 
-        let go = Go::new(&self.name, self.systems.clone())
-            .build(context)
-            .await?;
+    /// The store activation treats as its own when collecting stale links.
+    pub fn with_store(mut self, store: Store) -> Self {
+        self.store = store;
+        self
+    }
 
-        let k9s = K9s::new(&self.name, self.systems.clone())
-            .build(context)
-            .await?;
+    /// Whether the catalog, components or profile narrow the build, as they
+    /// do for `user-partial`.
+    fn partial(&self) -> bool {
+        !self.catalog || self.components.is_some() || self.profile.is_some()
+    }
 
-        let neovim = Neovim::new(&self.name, self.systems.clone())
-            .build(context)
-            .await?;
+    pub async fn build(self, context: &mut impl BuildContext) -> Result<String> {
+        let system = context.get_system();
 
-        let artifacts = binaries
-            .into_iter()
-            .chain(bat.0)
-            .chain(claude_code.0)
-            .chain(docket.0)
-            .chain(ghostty.0)
-            .chain(go.0)
-            .chain(k9s.0)
-            .chain(neovim.0)
-            .collect();
+        let config = match &self.config {
+            Some(config) => config,
+            None => &Config::load()?,
+        };
 
-        let symlinks_chain: Vec<(String, String)> = bat
-            .1
-            .into_iter()
-            .chain(claude_code.1)
-            .chain(docket.1)
-            .chain(ghostty.1)
-            .chain(go.1)
-            .chain(k9s.1)
-            .chain(neovim.1)
-            .collect();
+        let overlay = match &self.overlay {
+            Some(overlay) => overlay,
+            None => &Overlay::detect()?,
+        };
+
+        if let Some(component) = self.components.iter().flatten().find(|c| !c.compiled()) {
+            bail!(
+                "{} is not compiled in; build with the `{}` feature",
+                component.name(),
+                component.name()
+            );
+        }
+
+        let profile = self.profile.unwrap_or(config.profile);
+
+        let mut artifacts = if self.catalog {
+            utilities::build(context, profile, &config.tools).await?
+        } else {
+            vec![]
+        };
+        let mut installs = vec![];
+
+        let environment = Environment::new()
+            .with_path_on(
+                PathEntry::absolute("/Applications/Obsidian.app/Contents/MacOS"),
+                &DARWIN_SYSTEMS,
+            )
+            .with_path_on(
+                PathEntry::absolute("/Applications/VMware Fusion.app/Contents/Library"),
+                &DARWIN_SYSTEMS,
+            );
+
+        #[cfg(feature = "bat")]
+        if self.builds(config, Component::Bat, system) {
+            let mut bat = Bat::new(&self.name, self.component_systems(Component::Bat));
+
+            if let Some(theme) = &config.bat.theme {
+                bat = bat.with_theme(theme);
+            }
+
+            let (bat_artifacts, bat_installs) = bat.build(context).await?;
+
+            artifacts.extend(bat_artifacts);
+            installs.extend(bat_installs);
+        }
+
+        #[cfg(feature = "claude-code")]
+        if self.builds(config, Component::ClaudeCode, system) {
+            let mut claude_code =
+                ClaudeCode::new(&self.name, self.component_systems(Component::ClaudeCode));
+
+            if let Some(roots) = &overlay.claude_code.sandbox_bare_repo_roots {
+                let roots = roots
+                    .iter()
+                    .map(|root| HomePath::new(root))
+                    .collect::<Result<Vec<_>>>()?;
+
+                claude_code = claude_code.with_sandbox_bare_repo_roots(roots);
+            }
+
+            let (claude_code_artifacts, claude_code_installs) = claude_code.build(context).await?;
+
+            artifacts.extend(claude_code_artifacts);
+            installs.extend(claude_code_installs);
+        }
+
+        #[cfg(feature = "docket")]
+        if self.builds(config, Component::Docket, system) {
+            let (docket_artifacts, docket_installs) =
+                Docket::new(&self.name, self.component_systems(Component::Docket))
+                    .build(context)
+                    .await?;
+
+            artifacts.extend(docket_artifacts);
+            installs.extend(docket_installs);
+        }
+
+        #[cfg(feature = "ghostty")]
+        if self.builds(config, Component::Ghostty, system) {
+            let mut ghostty = Ghostty::new(&self.name, self.component_systems(Component::Ghostty));
+
+            if let Some(opacity) = config.ghostty.background_opacity {
+                ghostty = ghostty.with_background_opacity(opacity);
+            }
 
-        let duplicates = duplicate_symlink_targets(&symlinks_chain);
+            if let Some(theme) = &config.ghostty.theme {
+                ghostty = ghostty.with_theme(theme);
+            }
+
+            // The overlay's font wins over the shared one.
+            if let Some(family) = overlay
+                .ghostty
+                .font_family
+                .as_ref()
+                .or(config.ghostty.font_family.as_ref())
+            {
+                ghostty = ghostty.with_font_family(family);
+            }
+
+            if let Some(size) = overlay.ghostty.font_size.or(config.ghostty.font_size) {
+                ghostty = ghostty.with_font_size(size);
+            }
+
+            let (ghostty_artifacts, ghostty_installs) = ghostty.build(context).await?;
+
+            artifacts.extend(ghostty_artifacts);
+            installs.extend(ghostty_installs);
+        }
+
+        #[cfg(feature = "go")]
+        let environment = if self.builds(config, Component::Go, system) {
+            let (go_artifacts, go_installs) =
+                Go::new(&self.name, self.component_systems(Component::Go))
+                    .build(context)
+                    .await?;
+
+            artifacts.extend(go_artifacts);
+            installs.extend(go_installs);
+            environment.with_environment(Go::environment())
+        } else {
+            environment
+        };
+
+        #[cfg(feature = "go-tools")]
+        let environment = if self.builds(config, Component::GoTools, system) {
+            let go_tools = GoTools::new(&self.name, self.component_systems(Component::GoTools))
+                .build(context)
+                .await?;
+
+            let environment = go_tools.iter().fold(environment, |environment, digest| {
+                environment.with_path(PathEntry::artifact_bin(
+                    &self.store,
+                    &StorePath::output(digest),
+                ))
+            });
+
+            artifacts.extend(go_tools);
+            environment
+        } else {
+            environment
+        };
+
+        #[cfg(feature = "k9s")]
+        if self.builds(config, Component::K9s, system) {
+            let (k9s_artifacts, k9s_installs) =
+                K9s::new(&self.name, self.component_systems(Component::K9s))
+                    .build(context)
+                    .await?;
+
+            artifacts.extend(k9s_artifacts);
+            installs.extend(k9s_installs);
+        }
+
+        #[cfg(feature = "neovim")]
+        let environment = if self.builds(config, Component::Neovim, system) {
+            let (neovim_artifacts, neovim_installs) =
+                Neovim::new(&self.name, self.component_systems(Component::Neovim))
+                    .build(context)
+                    .await?;
+
+            artifacts.extend(neovim_artifacts);
+            installs.extend(neovim_installs);
+            environment.with_environment(Neovim::environment())
+        } else {
+            environment
+        };
+
+        let mut environment = environment
+            .with_path(PathEntry::home(".vorpal/bin"))
+            .with_path(PathEntry::home(".local/bin"));
+
+        for entry in overlay.path_entries()? {
+            environment = environment.with_path(entry);
+        }
+
+        let (shell_env_artifacts, shell_env_installs) =
+            ShellEnv::new(&self.name, self.systems.clone(), environment.clone())
+                .build(context)
+                .await?;
+
+        artifacts.extend(shell_env_artifacts);
+        installs.extend(shell_env_installs);
+
+        let duplicates = duplicate_symlink_targets(&installs);
 
         if !duplicates.is_empty() {
             bail!(
@@ -107,51 +517,75 @@ impl UserEnvironment {
             );
         }
 
-        let symlinks: Vec<(&str, &str)> = symlinks_chain
+        let activation = Activation::new(&self.name, self.systems.clone())
+            .with_artifacts(artifacts.clone())
+            .with_installs(installs.clone())
+            .with_partial(self.partial())
+            .with_store(self.store.clone())
+            .build(context)
+            .await?;
+
+        artifacts.push(activation.clone());
+
+        installs.push(Install::link(
+            StorePath::output(&activation).join("bin/dotfiles-activate"),
+            HomePath::new(".local/bin/dotfiles-activate")?,
+        ));
+
+        // Copies and rendered installs are written by `dotfiles-activate`;
+        // everything else is a symlink Vorpal's own activation creates.
+        let symlinks = installs
             .iter()
-            .map(|(a, b)| (a.as_str(), b.as_str()))
+            .filter(|install| install.mode == InstallMode::Link)
+            .map(|install| (install.source.env_key(), install.target.shell()))
             .collect();
 
-        let path = [
-            "/Applications/Obsidian.app/Contents/MacOS",
-            "/Applications/VMware\\ Fusion.app/Contents/Library",
-            "${GOPATH}/bin",
-            "${HOME}/.vorpal/bin",
-            "${HOME}/.local/bin",
-        ];
+        let environments = environment.render(system)?;
 
-        let environments = [
-            "EDITOR=nvim",
-            "GOPATH=${HOME}/Development/language/go",
-            &format!("PATH={}:${{PATH}}", path.join(":")),
-        ];
+        let digest = context
+            .user_environment(
+                &self.name,
+                self.systems,
+                artifacts.clone(),
+                environments,
+                symlinks,
+            )
+            .await?;
 
-        artifact::UserEnvironment::new(&self.name, self.systems)
-            .with_artifacts(artifacts)
-            .with_environments(environments.into_iter().map(|k| k.to_string()).collect())
-            .with_symlinks(symlinks)
-            .build(context)
-            .await
+        context.graph().record_installs(&installs);
+
+        Ok(digest)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::duplicate_symlink_targets;
-
-    fn symlinks(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    use super::{
+        availability_matrix, config::Config, duplicate_symlink_targets, health_checks,
+        overlay::Overlay, Component, UserEnvironment, COMPONENTS,
+    };
+    use crate::user::utilities::CATALOG;
+    use crate::{
+        context::mock::MockContext, health::Probe, home::HomePath, install::Install,
+        snapshot::assert_snapshot, store::StorePath,
+    };
+    use vorpal_sdk::api::artifact::ArtifactSystem::{Aarch64Darwin, X8664Linux};
+
+    fn symlinks(pairs: &[(&str, &str)]) -> Vec<Install> {
         pairs
             .iter()
-            .map(|(source, target)| (source.to_string(), target.to_string()))
+            .map(|(artifact, target)| {
+                Install::link(StorePath::output(artifact), HomePath::new(target).unwrap())
+            })
             .collect()
     }
 
     #[test]
     fn distinct_targets_are_not_duplicates() {
         let links = symlinks(&[
-            ("/store/output/user/aaa", "${HOME}/.claude/agents"),
-            ("/store/output/user/bbb", "${HOME}/.claude/hooks"),
-            ("/store/output/user/ccc/bin", "${HOME}/.docket/bin"),
+            ("aaa", "${HOME}/.claude/agents"),
+            ("bbb", "${HOME}/.claude/hooks"),
+            ("ccc", "${HOME}/.docket/bin"),
         ]);
 
         assert!(duplicate_symlink_targets(&links).is_empty());
@@ -160,55 +594,228 @@ mod tests {
     #[test]
     fn two_artifacts_claiming_one_target_are_reported() {
         let links = symlinks(&[
-            ("/store/output/user/aaa", "${HOME}/.claude/skills"),
-            ("/store/output/user/bbb", "${HOME}/.claude/skills"),
+            ("aaa", "${HOME}/.claude/skills"),
+            ("bbb", "${HOME}/.claude/skills"),
         ]);
 
         assert_eq!(
             duplicate_symlink_targets(&links),
-            vec!["${HOME}/.claude/skills".to_string()]
+            vec!["~/.claude/skills".to_string()]
         );
     }
 
     #[test]
     fn a_repeated_target_is_reported_once_however_many_claim_it() {
         let links = symlinks(&[
-            ("/store/output/user/aaa", "${HOME}/.docket/config"),
-            ("/store/output/user/bbb", "${HOME}/.docket/config"),
-            ("/store/output/user/ccc", "${HOME}/.docket/config"),
+            ("aaa", "${HOME}/.docket/config"),
+            ("bbb", "${HOME}/.docket/config"),
+            ("ccc", "${HOME}/.docket/config"),
         ]);
 
         assert_eq!(
             duplicate_symlink_targets(&links),
-            vec!["${HOME}/.docket/config".to_string()]
+            vec!["~/.docket/config".to_string()]
         );
     }
 
     #[test]
     fn several_collisions_are_reported_in_sorted_order() {
         let links = symlinks(&[
-            ("/store/output/user/aaa", "${HOME}/.docket/config"),
-            ("/store/output/user/bbb", "${HOME}/.claude/agents"),
-            ("/store/output/user/ccc", "${HOME}/.docket/config"),
-            ("/store/output/user/ddd", "${HOME}/.claude/agents"),
+            ("aaa", "${HOME}/.docket/config"),
+            ("bbb", "${HOME}/.claude/agents"),
+            ("ccc", "${HOME}/.docket/config"),
+            ("ddd", "${HOME}/.claude/agents"),
         ]);
 
         assert_eq!(
             duplicate_symlink_targets(&links),
             vec![
-                "${HOME}/.claude/agents".to_string(),
-                "${HOME}/.docket/config".to_string(),
+                "~/.claude/agents".to_string(),
+                "~/.docket/config".to_string(),
             ]
         );
     }
 
+    #[test]
+    fn a_rendered_file_and_a_link_at_one_target_are_reported_however_spelt() {
+        let links = vec![
+            Install::link(
+                StorePath::output("aaa"),
+                HomePath::new("$HOME/.config/go/env").unwrap(),
+            ),
+            Install::render(
+                StorePath::output("bbb"),
+                HomePath::new("${HOME}/.config/go/env").unwrap(),
+            ),
+        ];
+
+        assert_eq!(
+            duplicate_symlink_targets(&links),
+            vec!["~/.config/go/env".to_string()]
+        );
+    }
+
     #[test]
     fn one_artifact_linked_to_two_destinations_is_allowed() {
-        let links = symlinks(&[
-            ("/store/output/user/aaa/bin", "${HOME}/.docket/bin"),
-            ("/store/output/user/aaa/config", "${HOME}/.docket/config"),
-        ]);
+        let links = vec![
+            Install::link(
+                StorePath::output("aaa").join("bin"),
+                HomePath::new(".docket/bin").unwrap(),
+            ),
+            Install::link(
+                StorePath::output("aaa").join("config"),
+                HomePath::new(".docket/config").unwrap(),
+            ),
+        ];
 
         assert!(duplicate_symlink_targets(&links).is_empty());
     }
+
+    #[test]
+    fn the_availability_matrix_matches_its_snapshot() {
+        let matrix = availability_matrix();
+
+        assert_snapshot("availability-matrix.md", &matrix);
+
+        for name in CATALOG
+            .iter()
+            .map(|entry| entry.name)
+            .chain(COMPONENTS.iter().map(|component| component.name()))
+        {
+            assert!(matrix.contains(&format!("\n| {name} |")), "{name}");
+        }
+    }
+
+    #[test]
+    fn every_tool_and_component_builds_somewhere() {
+        assert!(CATALOG.iter().all(|entry| !entry.systems.is_empty()));
+        assert!(COMPONENTS
+            .iter()
+            .all(|component| !component.systems().is_empty()));
+    }
+
+    #[test]
+    fn health_checks_cover_only_configured_and_compiled_components() {
+        let config = Config::parse("components = [\"go\", \"k9s\"]").unwrap();
+
+        let checks = health_checks(&config, X8664Linux).unwrap();
+
+        if !cfg!(feature = "go") {
+            assert!(checks.is_empty());
+            return;
+        }
+
+        assert_eq!(checks.len(), 2);
+        assert!(checks
+            .iter()
+            .all(|(component, _)| *component == Component::Go));
+        assert!(matches!(
+            &checks[0].1.probe,
+            Probe::File(file) if file.tilde() == "~/.config/go/env"
+        ));
+        assert!(matches!(
+            &checks[1].1.probe,
+            Probe::PrintsHomePath { expected, .. } if expected.tilde() == "~/.config/go/env"
+        ));
+    }
+
+    #[cfg(feature = "go-tools")]
+    #[tokio::test]
+    async fn go_tools_put_their_bundle_on_path_from_the_store() {
+        let config = Config::parse("components = [\"go-tools\"]").unwrap();
+        let mut context = MockContext::new(Aarch64Darwin);
+
+        UserEnvironment::new("user", vec![Aarch64Darwin])
+            .with_catalog(false)
+            .with_config(config)
+            .with_overlay(Overlay::default())
+            .with_store(crate::store::Store::new("/scratch/store"))
+            .build(&mut context)
+            .await
+            .unwrap();
+
+        let path = context.user_environments[0]
+            .environments
+            .iter()
+            .find(|assignment| assignment.starts_with("PATH="))
+            .unwrap();
+
+        assert!(
+            path.contains(
+                ":/scratch/store/artifact/output/library/user-go-tools/bin:${HOME}/.vorpal/bin:"
+            ),
+            "{path}"
+        );
+    }
+
+    #[tokio::test]
+    async fn the_context_graphs_every_registered_artifact() {
+        let config = Config::parse("components = [\"bat\", \"claude-code\", \"docket\"]").unwrap();
+        let mut context = MockContext::new(Aarch64Darwin);
+
+        UserEnvironment::new("user", vec![Aarch64Darwin])
+            .with_catalog(false)
+            .with_config(config)
+            .with_overlay(Overlay::default())
+            .build(&mut context)
+            .await
+            .unwrap();
+
+        for name in context.names() {
+            assert!(
+                context.graph.node(name).is_some(),
+                "{name} is not in the graph"
+            );
+        }
+
+        let user = context.graph.node("user").unwrap();
+        assert!(user.inputs.contains(&"user-activation".to_string()));
+
+        let json = context.graph.json().unwrap();
+        assert!(json.contains("\"target\": \"~/.local/bin/dotfiles-activate\""));
+    }
+
+    #[tokio::test]
+    async fn links_reach_the_user_environment_and_copies_do_not() {
+        let config = Config::parse("components = [\"bat\", \"claude-code\", \"docket\"]").unwrap();
+        let mut context = MockContext::new(Aarch64Darwin);
+
+        UserEnvironment::new("user", vec![Aarch64Darwin])
+            .with_catalog(false)
+            .with_config(config)
+            .with_overlay(Overlay::default())
+            .build(&mut context)
+            .await
+            .unwrap();
+
+        let environment = &context.user_environments[0];
+        let targets: Vec<&str> = environment
+            .symlinks
+            .iter()
+            .map(|(_, target)| target.as_str())
+            .collect();
+
+        assert!(targets.contains(&"${HOME}/.local/bin/dotfiles-activate"));
+        assert!(targets.contains(&"${HOME}/.config/dotfiles/env.zsh"));
+        assert_eq!(
+            targets.contains(&"${HOME}/.config/bat/config"),
+            cfg!(feature = "bat")
+        );
+        assert_eq!(
+            targets.contains(&"${HOME}/.docket/bin"),
+            cfg!(feature = "docket")
+        );
+        assert_eq!(
+            targets.contains(&"${HOME}/.docket/config"),
+            cfg!(feature = "docket")
+        );
+        assert!(!targets.contains(&"${HOME}/.claude/settings.json"));
+        assert!(environment
+            .artifacts
+            .contains(&"user-activation".to_string()));
+        assert!(context
+            .names()
+            .iter()
+            .all(|name| !CATALOG.iter().any(|entry| entry.name == *name)));
+    }
 }
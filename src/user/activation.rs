@@ -0,0 +1,229 @@
+use crate::{
+    context::BuildContext,
+    install::{Install, HOME_PLACEHOLDER},
+    store::Store,
+};
+use anyhow::Result;
+use indoc::formatdoc;
+use vorpal_sdk::api::artifact::ArtifactSystem;
+
+/// Runs after Vorpal's own activation and does what a symlink cannot: copies
+/// each `Copy` install out of the store, and writes each `Render` install as
+/// a real file with its placeholders expanded for whoever is activating. The
+/// store copy keeps the placeholder, so every user sharing a store builds the
+/// same artifact.
+///
+/// Before anything is replaced, a file at an install target is moved to a
+/// timestamped backup under `~/.local/state/dotfiles/backup/`, unless it is
+/// a link into the store or still exactly what the previous activation
+/// copied or rendered there; `dotfiles-activate restore` puts the most
+/// recent backup back. `dotfiles-activate backup` does only the first half,
+/// for running before Vorpal's activation creates its links.
+///
+/// Links the previous activation installed at targets this build dropped are
+/// removed once the new files are in place, if they still point into the
+/// store; anything else at such a target is only reported. A partial
+/// build's activation skips that collection and leaves the previous
+/// activation's record in place, since its manifest omits whatever the
+/// build left out.
+pub struct Activation {
+    artifacts: Vec<String>,
+    installs: Vec<Install>,
+    name: String,
+    partial: bool,
+    store: Store,
+    systems: Vec<ArtifactSystem>,
+}
+
+/// Escapes text for an unquoted heredoc, where `$`, backticks and
+/// backslashes are still live.
+fn escape_heredoc(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('$', "\\$")
+        .replace('`', "\\`")
+}
+
+/// The shell functions and subcommands, which know nothing about this build
+/// beyond what the header and `manifest` give them.
+const SCRIPT_BODY: &str = include_str!("activation_script.sh");
+
+/// The part of the script that knows nothing about this build but where
+/// the store is and whether the build is partial.
+fn script_header(store: &Store, partial: bool) -> String {
+    formatdoc! {r#"
+        #!/usr/bin/env bash
+        set -euo pipefail
+
+        store="{store}"
+        placeholder='{placeholder}'
+        partial={partial}
+    "#,
+        placeholder = HOME_PLACEHOLDER,
+        store = store.root().display(),
+    }
+}
+
+/// One tab-separated line per install, for an unquoted heredoc: the mode,
+/// the source and the target relative to `$HOME`. The source keeps its
+/// artifact key live so the build step resolves it to a store path; the
+/// target is escaped so it reaches the manifest as spelled.
+fn manifest_entries(installs: &[Install]) -> String {
+    installs
+        .iter()
+        .map(|install| {
+            format!(
+                "{}\t{}\t{}",
+                install.mode.as_str(),
+                install.source.env_key(),
+                escape_heredoc(install.target.relative())
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl Activation {
+    pub fn new(name: &str, systems: Vec<ArtifactSystem>) -> Self {
+        Self {
+            artifacts: vec![],
+            installs: vec![],
+            name: name.to_string(),
+            partial: false,
+            store: Store::default(),
+            systems,
+        }
+    }
+
+    /// Artifacts whose outputs the installs read from.
+    pub fn with_artifacts(mut self, artifacts: Vec<String>) -> Self {
+        self.artifacts = artifacts;
+        self
+    }
+
+    pub fn with_installs(mut self, installs: Vec<Install>) -> Self {
+        self.installs = installs;
+        self
+    }
+
+    /// Marks a build narrowed to some of the catalog or components, whose
+    /// activation must not collect or record against the full build's.
+    pub fn with_partial(mut self, partial: bool) -> Self {
+        self.partial = partial;
+        self
+    }
+
+    /// The store whose links garbage collection may remove.
+    pub fn with_store(mut self, store: Store) -> Self {
+        self.store = store;
+        self
+    }
+
+    pub async fn build(self, context: &mut impl BuildContext) -> Result<String> {
+        let step_script = formatdoc! {"
+            mkdir -p $VORPAL_OUTPUT/bin $VORPAL_OUTPUT/share/dotfiles
+
+            cat << EOF > $VORPAL_OUTPUT/share/dotfiles/manifest
+            {manifest}
+            EOF
+
+            cat << 'EOF' > $VORPAL_OUTPUT/bin/dotfiles-activate
+            {header}
+            manifest() {{
+                cat << 'MANIFEST'
+            EOF
+
+            cat $VORPAL_OUTPUT/share/dotfiles/manifest >> $VORPAL_OUTPUT/bin/dotfiles-activate
+
+            cat << 'EOF' >> $VORPAL_OUTPUT/bin/dotfiles-activate
+            MANIFEST
+            }}
+
+            {body}
+            EOF
+
+            chmod 755 $VORPAL_OUTPUT/bin/dotfiles-activate
+        ",
+            body = SCRIPT_BODY,
+            header = script_header(&self.store, self.partial),
+            manifest = manifest_entries(&self.installs),
+        };
+
+        let step = context
+            .shell(self.artifacts.clone(), vec![], step_script)
+            .await?;
+
+        let name = format!("{}-activation", self.name);
+
+        let digest = context
+            .artifact(&name, vec![step], vec![], self.systems)
+            .await?;
+
+        Ok(digest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape_heredoc, manifest_entries, script_header};
+    use crate::{
+        home::HomePath,
+        install::Install,
+        store::{Store, StorePath},
+    };
+
+    #[test]
+    fn the_manifest_lists_every_install_with_its_mode() {
+        let installs = vec![
+            Install::link(
+                StorePath::output("aaa"),
+                HomePath::new(".config/bat/config").unwrap(),
+            ),
+            Install::render(
+                StorePath::output("bbb").join("user-go-env"),
+                HomePath::new(".config/go/env").unwrap(),
+            ),
+            Install::copy(
+                StorePath::output("ccc").join("config"),
+                HomePath::new(".docket/config").unwrap(),
+            ),
+            Install::copy_writable(
+                StorePath::output("ddd"),
+                HomePath::new(".claude/settings.json").unwrap(),
+            ),
+        ];
+
+        assert_eq!(
+            manifest_entries(&installs),
+            "link\t$VORPAL_ARTIFACT_aaa\t.config/bat/config\n\
+             render\t$VORPAL_ARTIFACT_bbb/user-go-env\t.config/go/env\n\
+             copy\t$VORPAL_ARTIFACT_ccc/config\t.docket/config\n\
+             copy-writable\t$VORPAL_ARTIFACT_ddd\t.claude/settings.json"
+        );
+    }
+
+    #[test]
+    fn targets_reach_the_manifest_as_spelled() {
+        let installs = vec![Install::link(
+            StorePath::output("aaa"),
+            HomePath::new("Library/Application Support/$app/`config`").unwrap(),
+        )];
+
+        assert_eq!(
+            manifest_entries(&installs),
+            "link\t$VORPAL_ARTIFACT_aaa\tLibrary/Application Support/\\$app/\\`config\\`"
+        );
+        assert_eq!(escape_heredoc("a\\b"), "a\\\\b");
+    }
+
+    #[test]
+    fn the_header_names_the_store_and_the_home_placeholder() {
+        let header = script_header(&Store::default(), false);
+
+        assert!(header.contains("store=\"/var/lib/vorpal/store\""));
+        assert!(header.contains("placeholder='@HOME@'"));
+        assert!(header.contains("partial=false"));
+        assert!(script_header(&Store::new("/scratch/store"), false)
+            .contains("store=\"/scratch/store\""));
+        assert!(script_header(&Store::default(), true).contains("partial=true"));
+    }
+}
@@ -0,0 +1,190 @@
+# Body of `dotfiles-activate`. The build prepends a header that sets
+# `store`, `placeholder` and `partial` and defines `manifest`, which prints
+# this build's installs as tab-separated `mode source target` lines with
+# the target relative to $HOME.
+
+# sed replacement text: escape the delimiter, `&` and backslashes.
+home=$(printf '%s' "$HOME" | sed 's/[|&\\]/\\&/g')
+
+state="$HOME/.local/state/dotfiles"
+backups="$state/backup"
+store_real=$(cd "$store" 2>/dev/null && pwd -P || printf '%s' "$store")
+
+# Clears the way for a new file or tree at $1, including a read-only
+# copy left by the previous activation.
+replace() {
+    if [ -d "$1" ] && [ ! -L "$1" ]; then
+        chmod -R u+w "$1"
+    fi
+    rm -rf "$1"
+}
+
+copy() {
+    mkdir -p "$(dirname "$2")"
+    replace "$2.dotfiles-activate"
+    cp -R "$1" "$2.dotfiles-activate"
+    if [ "$3" = writable ]; then
+        chmod -R u+w "$2.dotfiles-activate"
+    else
+        chmod -R a-w "$2.dotfiles-activate"
+    fi
+    replace "$2"
+    mv -f "$2.dotfiles-activate" "$2"
+}
+
+# Prints the file at $1 with the placeholder expanded.
+expand() {
+    sed "s|${placeholder}|${home}|g" "$1"
+}
+
+render() {
+    mkdir -p "$(dirname "$2")"
+    expand "$1" > "$2.dotfiles-activate"
+    replace "$2"
+    mv -f "$2.dotfiles-activate" "$2"
+}
+
+# Whether $1 is itself a link into the store.
+store_link() {
+    [ -L "$1" ] || return 1
+    case "$(readlink "$1")" in
+        "$store"/* | "$store_real"/*) return 0 ;;
+    esac
+    return 1
+}
+
+# Whether $1 is a link into the store, or sits inside one.
+in_store() {
+    store_link "$1" && return 0
+    case "$(cd "$(dirname "$1")" && pwd -P)/" in
+        "$store"/* | "$store_real"/*) return 0 ;;
+    esac
+    return 1
+}
+
+# Whether ~/$1 still holds exactly what the previous activation copied or
+# rendered there, so replacing it loses nothing. A file edited since, or
+# one whose previous source has left the store, does not count.
+unchanged() {
+    local mode source path="$HOME/$1"
+    [ -f "$state/manifest" ] && [ ! -L "$path" ] || return 1
+    IFS=$'\t' read -r mode source _ < <(
+        awk -F '\t' -v target="$1" '$1 != "link" && $3 == target' "$state/manifest"
+    ) || return 1
+    [ -e "$source" ] || return 1
+    case "$mode" in
+        copy | copy-writable) diff -r -q "$source" "$path" > /dev/null 2>&1 ;;
+        render) [ -f "$path" ] && expand "$source" | cmp -s - "$path" ;;
+        *) return 1 ;;
+    esac
+}
+
+# Drops ~/$1 from the previous activation's record, so the next
+# activation backs up whatever is there instead of overwriting it.
+forget() {
+    [ -f "$state/manifest" ] || return 0
+    awk -F '\t' -v target="$1" '$3 != target' "$state/manifest" > "$state/manifest.tmp"
+    mv -f "$state/manifest.tmp" "$state/manifest"
+}
+
+# Whether this build installs anything at ~/$1.
+listed() {
+    manifest | awk -F '\t' -v target="$1" '$3 == target { found = 1 } END { exit !found }'
+}
+
+# Removes what the previous activation linked at targets this build
+# no longer installs, as long as it is still a link into the store.
+# Anything else found there is reported and left alone.
+gc() {
+    [ -f "$state/manifest" ] || return 0
+    while IFS=$'\t' read -r _ _ target; do
+        [ -n "$target" ] || continue
+        path="$HOME/$target"
+        if listed "$target" || { [ ! -e "$path" ] && [ ! -L "$path" ]; }; then
+            continue
+        fi
+        if store_link "$path"; then
+            rm -f "$path"
+            echo "dotfiles-activate: removed stale link ~/$target"
+        else
+            echo "dotfiles-activate: ~/$target is no longer installed; left in place" >&2
+        fi
+    done < "$state/manifest"
+}
+
+# Moves every file at an install target that activation would otherwise
+# lose into a timestamped backup, listing the targets for `restore`: all
+# but links into the store and untouched copies and renders.
+backup() {
+    local stamp
+    stamp=$(date -u +%Y%m%dT%H%M%SZ)
+    while IFS=$'\t' read -r _ _ target; do
+        [ -n "$target" ] || continue
+        path="$HOME/$target"
+        if [ ! -e "$path" ] && [ ! -L "$path" ]; then
+            continue
+        fi
+        if in_store "$path" || unchanged "$target"; then
+            continue
+        fi
+        mkdir -p "$(dirname "$backups/$stamp/files/$target")"
+        mv "$path" "$backups/$stamp/files/$target"
+        printf '%s\n' "$target" >> "$backups/$stamp/targets"
+        echo "dotfiles-activate: backed up ~/$target to $backups/$stamp"
+    done < <(manifest)
+}
+
+# Puts back the files one backup moved away, the most recent unless
+# a timestamp is given.
+restore() {
+    local stamp="${1:-}"
+    if [ -z "$stamp" ]; then
+        stamp=$(ls -1 "$backups" 2>/dev/null | sort | tail -n 1) || true
+    fi
+    if [ -z "$stamp" ] || [ ! -f "$backups/$stamp/targets" ]; then
+        echo "dotfiles-activate: no backup ${stamp:+named $stamp }to restore" >&2
+        exit 1
+    fi
+    while IFS= read -r target; do
+        path="$HOME/$target"
+        replace "$path"
+        mkdir -p "$(dirname "$path")"
+        mv "$backups/$stamp/files/$target" "$path"
+        forget "$target"
+        echo "dotfiles-activate: restored ~/$target"
+    done < "$backups/$stamp/targets"
+    rm -rf "${backups:?}/$stamp"
+}
+
+case "${1:-activate}" in
+    activate)
+        backup
+        while IFS=$'\t' read -r mode source target; do
+            [ -n "$target" ] || continue
+            case "$mode" in
+                copy) copy "$source" "$HOME/$target" read-only ;;
+                copy-writable) copy "$source" "$HOME/$target" writable ;;
+                render) render "$source" "$HOME/$target" ;;
+            esac
+        done < <(manifest)
+        # A partial build's manifest omits what it left out, so it neither
+        # collects those links nor replaces the full build's record. `gc`
+        # reads the previous activation's record, so it runs before this
+        # one overwrites it.
+        if [ "$partial" != true ]; then
+            gc
+            mkdir -p "$state"
+            manifest > "$state/manifest"
+        fi
+        ;;
+    backup)
+        backup
+        ;;
+    restore)
+        restore "${2:-}"
+        ;;
+    *)
+        echo "usage: dotfiles-activate [activate | backup | restore [<timestamp>]]" >&2
+        exit 2
+        ;;
+esac
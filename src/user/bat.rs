@@ -1,7 +1,13 @@
-use crate::file::{FileCreate, FileSource};
+use crate::{
+    context::BuildContext,
+    file::{FileCreate, FileSource},
+    home::HomePath,
+    install::Install,
+    store::StorePath,
+};
 use anyhow::Result;
 use vorpal_artifacts::artifact::bat;
-use vorpal_sdk::{api::artifact::ArtifactSystem, artifact::get_env_key, context::ConfigContext};
+use vorpal_sdk::api::artifact::ArtifactSystem;
 
 struct BatConfig {
     name: String,
@@ -35,16 +41,23 @@ impl Bat {
         self
     }
 
+    fn config(&self) -> BatConfig {
+        let config = BatConfig::new(&self.name, self.systems.clone());
+
+        match &self.theme {
+            Some(theme) => config.with_theme(theme),
+            None => config,
+        }
+    }
+
     pub async fn build(
         self,
-        context: &mut ConfigContext,
-    ) -> Result<(Vec<String>, Vec<(String, String)>)> {
+        context: &mut impl BuildContext,
+    ) -> Result<(Vec<String>, Vec<Install>)> {
         let mut artifacts = vec![];
-        let mut symlinks = vec![];
-
-        let mut config_builder = BatConfig::new(&self.name, self.systems.clone());
+        let mut installs = vec![];
 
-        if let Some(theme_name) = self.theme {
+        if self.theme.is_some() {
             let config_theme_path = "https://raw.githubusercontent.com/folke/tokyonight.nvim/refs/tags/v4.14.1/extras/sublime/tokyonight_night.tmTheme";
             let config_theme = BatTheme::new(&self.name, config_theme_path, self.systems.clone())
                 .build(context)
@@ -52,26 +65,27 @@ impl Bat {
 
             artifacts.push(config_theme.clone());
 
-            config_builder = config_builder.with_theme(&theme_name);
-
-            symlinks.push((
-                format!("{}/tokyonight_night.tmTheme", get_env_key(&config_theme)),
-                "${HOME}/.config/bat/themes/tokyonight.tmTheme".to_string(),
+            installs.push(Install::link(
+                StorePath::output(&config_theme).join("tokyonight_night.tmTheme"),
+                HomePath::new(".config/bat/themes/tokyonight.tmTheme")?,
             ));
         }
 
-        let binary = bat::Bat::new().build(context).await?;
-        let config = config_builder.build(context).await?;
+        let binary = context
+            .external("bat", |context| Box::pin(bat::Bat::new().build(context)))
+            .await?;
 
-        symlinks.push((
-            format!("{}/{}-bat-config", get_env_key(&config), self.name),
-            "${HOME}/.config/bat/config".to_string(),
+        let config = self.config().build(context).await?;
+
+        installs.push(Install::link(
+            FileCreate::output_file(&config, &format!("{}-bat-config", self.name)),
+            HomePath::new(".config/bat/config")?,
         ));
 
         artifacts.push(binary);
         artifacts.push(config);
 
-        Ok((artifacts, symlinks))
+        Ok((artifacts, installs))
     }
 }
 
@@ -89,15 +103,22 @@ impl BatConfig {
         self
     }
 
-    pub async fn build(self, context: &mut ConfigContext) -> Result<String> {
+    /// The config file: bat's own command-line flags, one per line.
+    pub fn render(&self) -> String {
         let mut content = String::new();
 
-        if let Some(theme) = self.theme {
+        if let Some(theme) = &self.theme {
             let content_theme = format!("--theme={theme}");
 
             content.push_str(&content_theme);
         }
 
+        content
+    }
+
+    pub async fn build(self, context: &mut impl BuildContext) -> Result<String> {
+        let content = self.render();
+
         FileCreate::new(&format!("{}-bat-config", self.name), self.systems, &content)
             .build(context)
             .await
@@ -113,7 +134,7 @@ impl BatTheme {
         }
     }
 
-    pub async fn build(self, context: &mut ConfigContext) -> Result<String> {
+    pub async fn build(self, context: &mut impl BuildContext) -> Result<String> {
         FileSource::new(
             &format!("{}-bat-theme", &self.name),
             &self.path,
@@ -123,3 +144,80 @@ impl BatTheme {
         .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Bat;
+    use crate::{context::mock::MockContext, snapshot::assert_snapshot};
+    use vorpal_sdk::api::artifact::ArtifactSystem::Aarch64Darwin;
+
+    #[tokio::test]
+    async fn the_tokyonight_theme_is_fetched_configured_and_linked() {
+        let mut context = MockContext::new(Aarch64Darwin);
+
+        let (artifacts, installs) = Bat::new("user", vec![Aarch64Darwin])
+            .with_theme("tokyonight")
+            .build(&mut context)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            artifacts,
+            vec![
+                "user-bat-theme-file-source",
+                "bat",
+                "user-bat-config-file-create"
+            ]
+        );
+        assert!(context
+            .registered("user-bat-config-file-create")
+            .script()
+            .contains("--theme=tokyonight"));
+        assert!(context.registered("user-bat-theme-file-source").sources[0]
+            .path
+            .ends_with("/tokyonight_night.tmTheme"));
+        assert!(context.registered("bat").external);
+
+        assert_eq!(
+            installs
+                .iter()
+                .map(|install| (install.source.env_key(), install.target.tilde()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "$VORPAL_ARTIFACT_user-bat-theme-file-source/tokyonight_night.tmTheme"
+                        .to_string(),
+                    "~/.config/bat/themes/tokyonight.tmTheme".to_string()
+                ),
+                (
+                    "$VORPAL_ARTIFACT_user-bat-config-file-create/user-bat-config".to_string(),
+                    "~/.config/bat/config".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn without_a_theme_only_the_config_is_linked() {
+        let mut context = MockContext::new(Aarch64Darwin);
+
+        let (_, installs) = Bat::new("user", vec![Aarch64Darwin])
+            .build(&mut context)
+            .await
+            .unwrap();
+
+        assert_eq!(installs.len(), 1);
+        assert_eq!(context.names(), vec!["bat", "user-bat-config-file-create"]);
+        assert!(!context
+            .registered("user-bat-config-file-create")
+            .script()
+            .contains("--theme"));
+    }
+
+    #[test]
+    fn the_config_matches_its_snapshot() {
+        let config = Bat::new("user", vec![]).with_theme("tokyonight").config();
+
+        assert_snapshot("bat-config", &config.render());
+    }
+}
@@ -1,6 +1,14 @@
-use crate::file::{FileCreate, FileSource};
+use crate::{
+    context::BuildContext,
+    file::{FileCreate, FileSource},
+    health::{Check, Probe},
+    home::HomePath,
+    install::Install,
+    store::StorePath,
+    DARWIN_SYSTEMS,
+};
 use anyhow::Result;
-use vorpal_sdk::{api::artifact::ArtifactSystem, artifact::get_env_key, context::ConfigContext};
+use vorpal_sdk::api::artifact::ArtifactSystem;
 
 mod settings;
 
@@ -106,6 +114,7 @@ const SANDBOX_TOOLCHAIN_CACHE_PATHS: &[&str] = &[
 
 pub struct ClaudeCode {
     name: String,
+    sandbox_bare_repo_roots: Option<Vec<HomePath>>,
     systems: Vec<ArtifactSystem>,
 }
 
@@ -117,8 +126,8 @@ fn component_name(user: &str, component: &str) -> String {
 }
 
 /// Install destination for one entry under the user's Claude Code directory.
-fn claude_home(entry: &str) -> String {
-    format!("${{HOME}}/.claude/{entry}")
+fn claude_home(entry: &str) -> Result<HomePath> {
+    HomePath::new(&format!(".claude/{entry}"))
 }
 
 /// Permission patterns in a stable order, so the generated settings file does
@@ -156,29 +165,38 @@ impl ClaudeCode {
     pub fn new(name: &str, systems: Vec<ArtifactSystem>) -> Self {
         Self {
             name: name.to_string(),
+            sandbox_bare_repo_roots: None,
             systems,
         }
     }
 
-    pub async fn build(
-        self,
-        context: &mut ConfigContext,
-    ) -> Result<(Vec<String>, Vec<(String, String)>)> {
-        let agents = FileSource::new(
-            &component_name(&self.name, "agents"),
-            "src/user/claude_code/agents",
-            self.systems.clone(),
-        )
-        .build(context)
-        .await?;
+    /// Org roots holding bare-repo layouts, for machines that keep their
+    /// checkouts somewhere other than `SANDBOX_BARE_REPO_ROOT`.
+    pub fn with_sandbox_bare_repo_roots(mut self, roots: Vec<HomePath>) -> Self {
+        self.sandbox_bare_repo_roots = Some(roots);
+        self
+    }
 
-        let hooks = FileSource::new(
-            &component_name(&self.name, "hooks"),
-            "src/user/claude_code/hooks",
-            self.systems.clone(),
-        )
-        .build(context)
-        .await?;
+    /// The sandbox hooks write outside the store; without these directories
+    /// they record nothing and every sandboxed session reports friction.
+    pub fn checks() -> Result<Vec<Check>> {
+        Ok(vec![
+            Check::fail(
+                "friction ledger is writable",
+                Probe::Writable(HomePath::new(SANDBOX_FRICTION_LEDGER_PATH)?),
+                "mkdir -p ~/.claude/friction",
+            ),
+            Check::warn(
+                "docs cache is writable",
+                Probe::Writable(HomePath::new(SANDBOX_DOCS_CACHE_PATH)?),
+                "mkdir -p ~/.claude/cache/docs",
+            ),
+        ])
+    }
+
+    /// `settings.json` as this user's Claude Code runs it on `system`.
+    fn settings(&self, system: ArtifactSystem) -> settings::ClaudeCodeSettings {
+        let darwin = DARWIN_SYSTEMS.contains(&system);
 
         let settings_builder = settings::ClaudeCodeSettings::new(&self.name, self.systems.clone())
             .with_always_thinking_enabled(true)
@@ -352,6 +370,9 @@ impl ClaudeCode {
             .with_permission_allow("Bash(go tool golangci-lint:*)")
             .with_permission_allow("Bash(go vet:*)")
             .with_permission_allow("Bash(gofmt:*)")
+            .with_permission_allow("Bash(golangci-lint:*)")
+            .with_permission_allow("Bash(govulncheck:*)")
+            .with_permission_allow("Bash(staticcheck:*)")
             .with_permission_allow("Bash(~/.claude/scripts/attach-probe:*)")
             .with_permission_allow("Bash(~/.claude/scripts/shadow-transcript-summary.sh:*)")
             .with_permission_allow("Bash(~/.claude/scripts/wave-usage:*)")
@@ -397,7 +418,7 @@ impl ClaudeCode {
                 .copied(),
         );
 
-        let settings = settings_builder
+        settings_builder
             // TRUE, and this is a HARD CONSTRAINT, not a default anyone should
             // tighten later. Strict sandbox mode (`false`) was tried and is
             // WRONG here: it does not make an agent safer, it makes it brittle.
@@ -483,12 +504,20 @@ impl ClaudeCode {
                 SANDBOX_TOOLCHAIN_CACHE_PATHS
                     .iter()
                     .chain(std::iter::once(&SANDBOX_AGENT_MEMORY_PATH))
-                    .chain(std::iter::once(&SANDBOX_BARE_REPO_ROOT))
-                    .chain(std::iter::once(&SANDBOX_DOCS_CACHE_PATH))
-                    .chain(std::iter::once(&SANDBOX_DOCKET_STORE_PATH))
-                    .chain(std::iter::once(&SANDBOX_DOCKET_TRUST_PATH))
-                    .chain(std::iter::once(&SANDBOX_FRICTION_LEDGER_PATH))
                     .map(|p| p.to_string())
+                    .chain(match &self.sandbox_bare_repo_roots {
+                        Some(roots) => roots.iter().map(HomePath::tilde).collect(),
+                        None => vec![SANDBOX_BARE_REPO_ROOT.to_string()],
+                    })
+                    .chain(
+                        [
+                            SANDBOX_DOCS_CACHE_PATH,
+                            SANDBOX_DOCKET_STORE_PATH,
+                            SANDBOX_DOCKET_TRUST_PATH,
+                            SANDBOX_FRICTION_LEDGER_PATH,
+                        ]
+                        .map(|p| p.to_string()),
+                    )
                     .collect(),
             )
             .with_sandbox_filesystem_deny_read(sandbox_filesystem_deny_read_paths())
@@ -519,22 +548,29 @@ impl ClaudeCode {
                 // starved spawns).
                 "vuln.go.dev".to_string(),
             ])
-            .with_sandbox_network_allow_unix_sockets(vec![
-                // The OrbStack docker daemon socket. `docker *` in
-                // excluded_commands above covers only direct invocations; the
-                // build gate's `make build-docker` -> `docker buildx build`
-                // runs with `make` on top, matches nothing, and the sandboxed
-                // client is denied connecting to the docker API at this socket
-                // while `docker version` from an unsandboxed seat succeeds
-                // (RUN-38 isolated write executors, 2026-08-20/21, DOT-439).
-                // Excluding `make *` instead would let every Makefile target
-                // run wholly outside the sandbox; this grants the one socket
-                // the evidence names — no more than the docker exclusion
-                // already concedes — and only helps once the daemon is up: a
-                // cold daemon is "no such file or directory", not a denial.
-                "~/.orbstack/run/docker.sock".to_string(),
-                "~/Library/Group Containers/2BUA8C4S2C.com.1password/t/agent.sock".to_string(),
-            ])
+            // Both sockets belong to macOS apps (OrbStack, 1Password); on
+            // Linux the grant would name paths that never exist.
+            .with_sandbox_network_allow_unix_sockets(if darwin {
+                vec![
+                    // The OrbStack docker daemon socket. `docker *` in
+                    // excluded_commands above covers only direct invocations;
+                    // the build gate's `make build-docker` -> `docker buildx
+                    // build` runs with `make` on top, matches nothing, and the
+                    // sandboxed client is denied connecting to the docker API
+                    // at this socket while `docker version` from an unsandboxed
+                    // seat succeeds (RUN-38 isolated write executors,
+                    // 2026-08-20/21, DOT-439). Excluding `make *` instead would
+                    // let every Makefile target run wholly outside the sandbox;
+                    // this grants the one socket the evidence names — no more
+                    // than the docker exclusion already concedes — and only
+                    // helps once the daemon is up: a cold daemon is "no such
+                    // file or directory", not a denial.
+                    "~/.orbstack/run/docker.sock".to_string(),
+                    "~/Library/Group Containers/2BUA8C4S2C.com.1password/t/agent.sock".to_string(),
+                ]
+            } else {
+                vec![]
+            })
             // enable_weaker_network_isolation is deliberately NOT set. It reads
             // like the fix for the Go TLS failure above, and it is not: the
             // sandboxing guide scopes it to "using httpProxyPort with a MITM
@@ -542,8 +578,29 @@ impl ClaudeCode {
             // it costs a documented data-exfiltration path. excluded_commands
             // above is the supported remedy for that failure.
             .with_sandbox_network_allow_local_binding(true)
-            .build(context)
-            .await?;
+    }
+
+    pub async fn build(
+        self,
+        context: &mut impl BuildContext,
+    ) -> Result<(Vec<String>, Vec<Install>)> {
+        let agents = FileSource::new(
+            &component_name(&self.name, "agents"),
+            "src/user/claude_code/agents",
+            self.systems.clone(),
+        )
+        .build(context)
+        .await?;
+
+        let hooks = FileSource::new(
+            &component_name(&self.name, "hooks"),
+            "src/user/claude_code/hooks",
+            self.systems.clone(),
+        )
+        .build(context)
+        .await?;
+
+        let settings = self.settings(context.get_system()).build(context).await?;
 
         let scripts = FileSource::new(
             &component_name(&self.name, "scripts"),
@@ -594,40 +651,33 @@ impl ClaudeCode {
         .build(context)
         .await?;
 
-        let symlinks = vec![
-            (get_env_key(&agents), claude_home("agents")),
-            (get_env_key(&hooks), claude_home("hooks")),
-            (
-                FileCreate::output_file_path(
-                    &get_env_key(&memory),
-                    &component_name(&self.name, "memory"),
-                ),
-                claude_home("CLAUDE.md"),
-            ),
-            (get_env_key(&scripts), claude_home("scripts")),
-            (
-                FileCreate::output_file_path(
-                    &get_env_key(&settings),
-                    &component_name(&self.name, "settings"),
-                ),
-                claude_home("settings.json"),
+        // Single-file components link to the file inside the artifact, which
+        // `FileCreate` names after the artifact, not to the output directory.
+        let file = |digest: &str, suffix: &str| {
+            FileCreate::output_file(digest, &component_name(&self.name, suffix))
+        };
+
+        let installs = vec![
+            Install::link(StorePath::output(&agents), claude_home("agents")?),
+            Install::link(StorePath::output(&hooks), claude_home("hooks")?),
+            Install::link(file(&memory, "memory"), claude_home("CLAUDE.md")?),
+            Install::link(StorePath::output(&scripts), claude_home("scripts")?),
+            // Claude Code saves permission grants back into settings.json,
+            // so it gets a writable copy; the next activation resets it.
+            Install::copy_writable(file(&settings, "settings"), claude_home("settings.json")?),
+            Install::link(StorePath::output(&skills), claude_home("skills")?),
+            Install::link(
+                file(&statusline, "statusline"),
+                claude_home("statusline.sh")?,
             ),
-            (get_env_key(&skills), claude_home("skills")),
-            (
-                FileCreate::output_file_path(
-                    &get_env_key(&statusline),
-                    &component_name(&self.name, "statusline"),
-                ),
-                claude_home("statusline.sh"),
-            ),
-            (get_env_key(&workflows), claude_home("workflows")),
+            Install::link(StorePath::output(&workflows), claude_home("workflows")?),
         ];
 
         let artifacts = vec![
             agents, hooks, memory, scripts, settings, skills, statusline, workflows,
         ];
 
-        Ok((artifacts, symlinks))
+        Ok((artifacts, installs))
     }
 }
 
@@ -635,10 +685,16 @@ impl ClaudeCode {
 mod tests {
     use super::{
         claude_home, component_name, sandbox_filesystem_deny_read_paths,
-        sorted_permission_patterns, SENSITIVE_PATHS, SENSITIVE_PATHS_DENY_EDIT_ONLY,
+        sorted_permission_patterns, ClaudeCode, SENSITIVE_PATHS, SENSITIVE_PATHS_DENY_EDIT_ONLY,
         SENSITIVE_PATHS_DENY_READ_ONLY,
     };
-    use crate::file::FileCreate;
+    use crate::{
+        file::FileCreate,
+        snapshot::assert_snapshot,
+        store::{Store, StorePath},
+    };
+    use std::path::Path;
+    use vorpal_sdk::api::artifact::ArtifactSystem::{Aarch64Darwin, X8664Linux};
 
     #[test]
     fn component_artifacts_are_namespaced_by_user_and_component() {
@@ -655,23 +711,24 @@ mod tests {
     #[test]
     fn install_destinations_live_under_the_home_claude_directory() {
         assert_eq!(
-            claude_home("settings.json"),
-            "${HOME}/.claude/settings.json"
+            claude_home("settings.json").unwrap().tilde(),
+            "~/.claude/settings.json"
         );
-        assert_eq!(claude_home("agents"), "${HOME}/.claude/agents");
+        assert_eq!(claude_home("agents").unwrap().tilde(), "~/.claude/agents");
     }
 
     #[test]
     fn single_file_components_link_to_the_file_not_the_artifact_directory() {
-        let output = "/var/lib/vorpal/store/artifact/output/user/abc123";
+        let store = Store::new("/scratch/store");
+        let output = StorePath::output("abc123");
 
-        let source = FileCreate::output_file_path(output, &component_name("user", "settings"));
+        let source = FileCreate::output_file("abc123", &component_name("user", "settings"));
 
         assert_eq!(
-            source,
-            "/var/lib/vorpal/store/artifact/output/user/abc123/user-claude-code-settings"
+            store.resolve(&source),
+            Path::new("/scratch/store/artifact/output/library/abc123/user-claude-code-settings")
         );
-        assert_ne!(source, output);
+        assert_ne!(store.resolve(&source), store.resolve(&output));
     }
 
     #[test]
@@ -739,4 +796,16 @@ mod tests {
 
         assert_eq!(denied, expected);
     }
+
+    #[test]
+    fn settings_match_their_snapshots() {
+        let claude_code = ClaudeCode::new("user", vec![]);
+
+        for (system, name) in [
+            (Aarch64Darwin, "claude-code-settings.darwin.json"),
+            (X8664Linux, "claude-code-settings.linux.json"),
+        ] {
+            assert_snapshot(name, &claude_code.settings(system).render().unwrap());
+        }
+    }
 }
@@ -1,9 +1,9 @@
-use crate::file::FileCreate;
+use crate::{context::BuildContext, file::FileCreate};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::BTreeMap;
-use vorpal_sdk::{api::artifact::ArtifactSystem, context::ConfigContext};
+use vorpal_sdk::api::artifact::ArtifactSystem;
 
 // =========================================================================
 // Supporting types for nested configuration structures
@@ -2373,9 +2373,14 @@ impl ClaudeCodeSettings {
     // Build
     // =====================================================================
 
-    pub async fn build(self, context: &mut ConfigContext) -> Result<String> {
-        let json_content = serde_json::to_string_pretty(&self)
-            .map_err(|e| anyhow::anyhow!("Failed to serialize Claude Code settings: {}", e))?;
+    /// The `settings.json` Claude Code reads.
+    pub fn render(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| anyhow::anyhow!("Failed to serialize Claude Code settings: {}", e))
+    }
+
+    pub async fn build(self, context: &mut impl BuildContext) -> Result<String> {
+        let json_content = self.render()?;
 
         FileCreate::new(
             &format!("{}-claude-code-settings", self.name),
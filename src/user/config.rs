@@ -0,0 +1,137 @@
+use crate::user::{
+    utilities::{Profile, CATALOG},
+    Component,
+};
+use anyhow::{bail, Result};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+
+const DOTFILES_TOML: &str = include_str!("dotfiles.toml");
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct BatOptions {
+    /// Only `tokyonight` ships a theme file; any other name must be one bat
+    /// already knows.
+    pub theme: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct GhosttyOptions {
+    pub background_opacity: Option<f32>,
+    pub font_family: Option<String>,
+    pub font_size: Option<u8>,
+    pub theme: Option<String>,
+}
+
+/// `dotfiles.toml`: which components and tools make up the user environment
+/// and the routine options of each. The builders stay in Rust; this picks
+/// what they build.
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub bat: BatOptions,
+    pub components: Vec<Component>,
+    #[serde(default)]
+    pub ghostty: GhosttyOptions,
+    #[serde(default)]
+    pub profile: Profile,
+    /// Catalog tools built in addition to the profile's.
+    #[serde(default)]
+    pub tools: Vec<String>,
+}
+
+impl Config {
+    pub fn load() -> Result<Self> {
+        Self::parse(DOTFILES_TOML)
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let config: Self = toml::from_str(content)?;
+
+        let mut components = BTreeSet::new();
+
+        for component in &config.components {
+            if !components.insert(component.name()) {
+                bail!("dotfiles.toml lists component {} twice", component.name());
+            }
+        }
+
+        for tool in &config.tools {
+            if !CATALOG.iter().any(|entry| entry.name == *tool) {
+                bail!("dotfiles.toml: {tool} is not a tool in the catalog");
+            }
+        }
+
+        if let Some(opacity) = config.ghostty.background_opacity {
+            if !(0.0..=1.0).contains(&opacity) {
+                bail!("dotfiles.toml: ghostty background_opacity {opacity} is not within 0 to 1");
+            }
+        }
+
+        Ok(config)
+    }
+
+    pub fn has_component(&self, component: Component) -> bool {
+        self.components.contains(&component)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Config;
+    use crate::user::{utilities::Profile, Component, COMPONENTS};
+
+    #[test]
+    fn the_checked_in_config_is_valid() {
+        let config = Config::load().unwrap();
+
+        assert_eq!(config.profile, Profile::Workstation);
+        assert!(COMPONENTS
+            .iter()
+            .all(|component| config.has_component(*component)));
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        let error = Config::parse("components = []\n[ghostty]\nfont = 13").unwrap_err();
+
+        assert!(
+            error.to_string().contains("unknown field `font`"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn unknown_components_are_rejected() {
+        assert!(Config::parse("components = [\"emacs\"]").is_err());
+    }
+
+    #[test]
+    fn repeated_components_are_rejected() {
+        assert!(Config::parse("components = [\"bat\", \"bat\"]").is_err());
+    }
+
+    #[test]
+    fn tools_must_be_in_the_catalog() {
+        assert!(Config::parse("components = []\ntools = [\"ripgrep\"]").is_ok());
+        assert!(Config::parse("components = []\ntools = [\"rg\"]").is_err());
+    }
+
+    #[test]
+    fn components_parse_from_their_kebab_case_names() {
+        let config = Config::parse("components = [\"claude-code\", \"go-tools\"]").unwrap();
+
+        assert_eq!(
+            config.components,
+            vec![Component::ClaudeCode, Component::GoTools]
+        );
+    }
+
+    #[test]
+    fn opacity_outside_zero_to_one_is_rejected() {
+        assert!(Config::parse("components = []\n[ghostty]\nbackground_opacity = 1.5").is_err());
+    }
+}
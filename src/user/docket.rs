@@ -1,6 +1,13 @@
-use crate::file::FileSource;
+use crate::{
+    context::BuildContext,
+    file::FileSource,
+    health::{Check, Probe},
+    home::HomePath,
+    install::Install,
+    store::StorePath,
+};
 use anyhow::Result;
-use vorpal_sdk::{api::artifact::ArtifactSystem, artifact::get_env_key, context::ConfigContext};
+use vorpal_sdk::api::artifact::ArtifactSystem;
 
 pub struct Docket {
     name: String,
@@ -15,10 +22,27 @@ impl Docket {
         }
     }
 
+    /// Every docket verb opens `~/.docket/issues.db` read-write, and the
+    /// trust verbs lock under `~/.config/docket`.
+    pub fn checks() -> Result<Vec<Check>> {
+        Ok(vec![
+            Check::fail(
+                "store can be created",
+                Probe::Creatable(HomePath::new(".docket")?),
+                "make ~ writable, or create ~/.docket owned by you",
+            ),
+            Check::warn(
+                "trust store can be created",
+                Probe::Creatable(HomePath::new(".config/docket")?),
+                "make ~/.config writable, or create ~/.config/docket owned by you",
+            ),
+        ])
+    }
+
     pub async fn build(
         self,
-        context: &mut ConfigContext,
-    ) -> Result<(Vec<String>, Vec<(String, String)>)> {
+        context: &mut impl BuildContext,
+    ) -> Result<(Vec<String>, Vec<Install>)> {
         // One artifact holding the whole shared corpus. The source tree
         // mirrors the installed tree: config/{contracts,fragments,schemas,
         // workflows,policy.toml} is what the engine scans as its shared
@@ -35,17 +59,17 @@ impl Docket {
         .build(context)
         .await?;
 
-        let symlinks = vec![
-            (
-                format!("{}/bin", get_env_key(&corpus)),
-                "${HOME}/.docket/bin".to_string(),
+        let installs = vec![
+            Install::link(
+                StorePath::output(&corpus).join("bin"),
+                HomePath::new(".docket/bin")?,
             ),
-            (
-                format!("{}/config", get_env_key(&corpus)),
-                "${HOME}/.docket/config".to_string(),
+            Install::link(
+                StorePath::output(&corpus).join("config"),
+                HomePath::new(".docket/config")?,
             ),
         ];
 
-        Ok((vec![corpus], symlinks))
+        Ok((vec![corpus], installs))
     }
 }
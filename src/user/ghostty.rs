@@ -1,7 +1,7 @@
-use crate::file::FileCreate;
+use crate::{context::BuildContext, file::FileCreate, home::HomePath, install::Install};
 use anyhow::Result;
 use indoc::formatdoc;
-use vorpal_sdk::{api::artifact::ArtifactSystem, artifact::get_env_key, context::ConfigContext};
+use vorpal_sdk::api::artifact::ArtifactSystem;
 
 struct GhosttyConfig {
     background_opacity: f32,
@@ -14,8 +14,12 @@ struct GhosttyConfig {
 }
 
 pub struct Ghostty {
+    background_opacity: f32,
+    font_family: String,
+    font_size: u8,
     name: String,
     systems: Vec<ArtifactSystem>,
+    theme: String,
 }
 
 impl GhosttyConfig {
@@ -56,8 +60,9 @@ impl GhosttyConfig {
         self
     }
 
-    pub async fn build(self, context: &mut ConfigContext) -> Result<String> {
-        let content = formatdoc! {"
+    /// The config file, one `key = value` per line.
+    pub fn render(&self) -> String {
+        formatdoc! {"
             background-opacity = {background_opacity}
             font-family = {font_family}
             font-size = {font_size}
@@ -69,7 +74,11 @@ impl GhosttyConfig {
             font_size = self.font_size,
             macos_option_as_alt = self.macos_option_as_alt,
             theme = self.theme
-        };
+        }
+    }
+
+    pub async fn build(self, context: &mut impl BuildContext) -> Result<String> {
+        let content = self.render();
 
         FileCreate::new(
             &format!("{}-ghostty-config", self.name),
@@ -84,33 +93,73 @@ impl GhosttyConfig {
 impl Ghostty {
     pub fn new(name: &str, systems: Vec<ArtifactSystem>) -> Self {
         Self {
+            background_opacity: 0.95,
+            font_family: "GeistMono NFM".to_string(),
+            font_size: 16,
             name: name.to_string(),
             systems,
+            theme: "TokyoNight".to_string(),
         }
     }
 
+    pub fn with_background_opacity(mut self, opacity: f32) -> Self {
+        self.background_opacity = opacity;
+        self
+    }
+
+    pub fn with_font_family(mut self, family: &str) -> Self {
+        self.font_family = family.to_string();
+        self
+    }
+
+    pub fn with_font_size(mut self, size: u8) -> Self {
+        self.font_size = size;
+        self
+    }
+
+    pub fn with_theme(mut self, theme: &str) -> Self {
+        self.theme = theme.to_string();
+        self
+    }
+
+    fn config(&self) -> GhosttyConfig {
+        GhosttyConfig::new(&self.name, self.systems.clone())
+            .with_background_opacity(self.background_opacity)
+            .with_font_family(&self.font_family)
+            .with_font_size(self.font_size)
+            .with_macos_option_as_alt(true)
+            .with_theme(&self.theme)
+    }
+
     pub async fn build(
         self,
-        context: &mut ConfigContext,
-    ) -> Result<(Vec<String>, Vec<(String, String)>)> {
+        context: &mut impl BuildContext,
+    ) -> Result<(Vec<String>, Vec<Install>)> {
         let mut artifacts = vec![];
 
-        let config = GhosttyConfig::new(&self.name, self.systems.clone())
-            .with_background_opacity(0.95)
-            .with_font_family("GeistMono NFM")
-            .with_font_size(16)
-            .with_macos_option_as_alt(true)
-            .with_theme("TokyoNight")
-            .build(context)
-            .await?;
+        let config = self.config().build(context).await?;
 
-        let symlinks = vec![(
-            format!("{}/{}-ghostty-config", get_env_key(&config), self.name),
-            "${HOME}/Library/Application\\ Support/com.mitchellh.ghostty/config".to_string(),
+        let installs = vec![Install::link(
+            FileCreate::output_file(&config, &format!("{}-ghostty-config", self.name)),
+            HomePath::new("Library/Application Support/com.mitchellh.ghostty/config")?,
         )];
 
         artifacts.push(config);
 
-        Ok((artifacts, symlinks))
+        Ok((artifacts, installs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ghostty;
+    use crate::snapshot::assert_snapshot;
+
+    #[test]
+    fn the_config_matches_its_snapshot() {
+        assert_snapshot(
+            "ghostty-config",
+            &Ghostty::new("user", vec![]).config().render(),
+        );
     }
 }
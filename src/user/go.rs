@@ -1,12 +1,105 @@
-use crate::file::FileCreate;
-use anyhow::Result;
-use vorpal_sdk::{api::artifact::ArtifactSystem, artifact::get_env_key, context::ConfigContext};
+use crate::{
+    context::BuildContext,
+    environment::{Environment, PathEntry, Value},
+    file::FileCreate,
+    health::{Check, Probe},
+    home::HomePath,
+    install::{Install, HOME_PLACEHOLDER},
+};
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+use vorpal_sdk::api::artifact::{
+    ArtifactSystem,
+    ArtifactSystem::{Aarch64Darwin, Aarch64Linux, X8664Darwin, X8664Linux},
+};
+
+/// Variables this config may write to the Go env file. `go env -w` accepts
+/// more, but an unrecognised key here is far more likely a typo than a
+/// setting, and Go ignores a misspelt one without a word.
+const GO_ENV_KEYS: &[&str] = &[
+    "CGO_ENABLED",
+    "GOBIN",
+    "GOCACHE",
+    "GOFLAGS",
+    "GOINSECURE",
+    "GOMODCACHE",
+    "GONOPROXY",
+    "GONOSUMDB",
+    "GOPATH",
+    "GOPRIVATE",
+    "GOPROXY",
+    "GOSUMDB",
+    "GOTOOLCHAIN",
+];
+
+/// GOPATH relative to the home directory, shared by the shell environment
+/// and the Go env file.
+const GOPATH: &str = "Development/language/go";
+
+struct GoEnv {
+    name: String,
+    systems: Vec<ArtifactSystem>,
+    vars: BTreeMap<String, String>,
+}
 
 pub struct Go {
     name: String,
     systems: Vec<ArtifactSystem>,
 }
 
+/// Where `go env GOENV` looks on each platform: Go's config directory is
+/// `os.UserConfigDir()`, which differs between macOS and Linux.
+fn go_env_path(system: ArtifactSystem) -> Result<HomePath> {
+    match system {
+        Aarch64Darwin | X8664Darwin => HomePath::new("Library/Application Support/go/env"),
+        Aarch64Linux | X8664Linux => HomePath::new(".config/go/env"),
+        _ => bail!("no Go env location for {}", system.as_str_name()),
+    }
+}
+
+impl GoEnv {
+    pub fn new(name: &str, systems: Vec<ArtifactSystem>) -> Self {
+        Self {
+            name: name.to_string(),
+            systems,
+            vars: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_var(mut self, key: &str, value: &str) -> Self {
+        self.vars.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// The file `go env -w` writes: one `KEY=VALUE` per line, unquoted, with
+    /// no variable expansion.
+    pub fn render(&self) -> Result<String> {
+        let mut lines = vec![];
+
+        for (key, value) in &self.vars {
+            if !GO_ENV_KEYS.contains(&key.as_str()) {
+                bail!("{key} is not a Go env variable this config manages");
+            }
+
+            if value.contains('\n') {
+                bail!("Go env value for {key} spans more than one line");
+            }
+
+            lines.push(format!("{key}={value}"));
+        }
+
+        Ok(lines.join("\n"))
+    }
+
+    pub async fn build(self, context: &mut impl BuildContext) -> Result<String> {
+        let content = self.render()?;
+
+        FileCreate::new(&format!("{}-go-env", self.name), self.systems, &content)
+            .build(context)
+            .await
+    }
+}
+
 impl Go {
     pub fn new(name: &str, systems: Vec<ArtifactSystem>) -> Self {
         Self {
@@ -15,29 +108,114 @@ impl Go {
         }
     }
 
-    pub async fn build(
-        self,
-        context: &mut ConfigContext,
-    ) -> Result<(Vec<String>, Vec<(String, String)>)> {
+    /// GOPATH for shells, and its `bin` on PATH for `go install`ed tools.
+    pub fn environment() -> Environment {
+        Environment::new()
+            .with_var("GOPATH", Value::Path(PathEntry::home(GOPATH)))
+            .with_path(PathEntry::home(&format!("{GOPATH}/bin")))
+    }
+
+    /// The rendered env file only takes effect where `go env GOENV` looks,
+    /// and only exists once `dotfiles-activate` has run: Vorpal's own
+    /// activation creates links and nothing else.
+    pub fn checks(system: ArtifactSystem) -> Result<Vec<Check>> {
+        Ok(vec![
+            Check::fail(
+                "env file has been rendered",
+                Probe::File(go_env_path(system)?),
+                "run `~/.local/bin/dotfiles-activate` after `vorpal-activate`, or `just activate`",
+            ),
+            Check::fail(
+                "env file is where go env GOENV looks",
+                Probe::PrintsHomePath {
+                    args: vec!["env".to_string(), "GOENV".to_string()],
+                    expected: go_env_path(system)?,
+                    program: "go".to_string(),
+                },
+                "unset GOENV and run `just activate`, which renders the env file",
+            ),
+        ])
+    }
+
+    fn env(&self) -> GoEnv {
         // Go consults this file (`go env GOENV`) on EVERY invocation, shell or
-        // not. The GOPATH shell export in user.rs reaches only processes
+        // not. The GOPATH shell export from `environment` reaches only processes
         // descended from a profile-sourcing shell; anything else — a daemon's
         // subprocess, an engine gate — silently falls back to ~/go and
         // resolves the wrong module cache (DOT-329). Env files expand no
-        // variables, so the value must be the literal absolute path: resolved
-        // from the invoking user's HOME when this config is evaluated.
-        let home = std::env::var("HOME")?;
-        let content = format!("GOPATH={home}/Development/language/go");
+        // variables, so the value must be the literal absolute path. The
+        // artifact keeps the placeholder and activation renders the file
+        // with the activating user's HOME, so the digest is the same for
+        // every user.
+        let gopath = format!("{HOME_PLACEHOLDER}/{GOPATH}");
 
-        let env = FileCreate::new(&format!("{}-go-env", self.name), self.systems, &content)
-            .build(context)
-            .await?;
+        GoEnv::new(&self.name, self.systems.clone())
+            .with_var("GOMODCACHE", &format!("{gopath}/pkg/mod"))
+            .with_var("GOPATH", &gopath)
+            .with_var("GOPRIVATE", "github.com/ALT-F4-LLC/*")
+            .with_var("GOPROXY", "https://proxy.golang.org,direct")
+            // The toolchain on PATH is the pinned one. Letting a go.mod
+            // `toolchain` line download another would need the network
+            // mid-build, which a sandboxed gate does not have.
+            .with_var("GOTOOLCHAIN", "local")
+    }
 
-        let symlinks = vec![(
-            format!("{}/{}-go-env", get_env_key(&env), self.name),
-            "$HOME/Library/Application\\ Support/go/env".to_string(),
+    pub async fn build(
+        self,
+        context: &mut impl BuildContext,
+    ) -> Result<(Vec<String>, Vec<Install>)> {
+        let env = self.env().build(context).await?;
+
+        let installs = vec![Install::render(
+            FileCreate::output_file(&env, &format!("{}-go-env", self.name)),
+            go_env_path(context.get_system())?,
         )];
 
-        Ok((vec![env], symlinks))
+        Ok((vec![env], installs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{go_env_path, Go, GoEnv};
+    use crate::snapshot::assert_snapshot;
+    use vorpal_sdk::api::artifact::ArtifactSystem::{Aarch64Darwin, X8664Linux};
+
+    #[test]
+    fn env_file_lists_each_variable_once_in_key_order() {
+        let env = GoEnv::new("user", vec![])
+            .with_var("GOPROXY", "https://proxy.golang.org,direct")
+            .with_var("CGO_ENABLED", "0")
+            .with_var("GOPROXY", "off");
+
+        assert_eq!(env.render().unwrap(), "CGO_ENABLED=0\nGOPROXY=off");
+    }
+
+    #[test]
+    fn unknown_variables_are_rejected() {
+        let env = GoEnv::new("user", vec![]).with_var("GO_PATH", "/tmp/go");
+
+        assert!(env.render().is_err());
+    }
+
+    #[test]
+    fn multi_line_values_are_rejected() {
+        let env = GoEnv::new("user", vec![]).with_var("GOFLAGS", "-mod=mod\n-v");
+
+        assert!(env.render().is_err());
+    }
+
+    #[test]
+    fn env_file_installs_where_go_env_goenv_looks() {
+        assert_eq!(
+            go_env_path(Aarch64Darwin).unwrap().tilde(),
+            "~/Library/Application Support/go/env"
+        );
+        assert_eq!(go_env_path(X8664Linux).unwrap().tilde(), "~/.config/go/env");
+    }
+
+    #[test]
+    fn the_env_file_matches_its_snapshot() {
+        assert_snapshot("go-env", &Go::new("user", vec![]).env().render().unwrap());
     }
 }
@@ -0,0 +1,209 @@
+use crate::context::BuildContext;
+use anyhow::Result;
+use indoc::formatdoc;
+use vorpal_sdk::{
+    api::artifact::ArtifactSystem,
+    artifact::{get_env_key, language::go::Go, ArtifactSource},
+};
+
+/// One Go command pinned to a release tag, spelled exactly as the repository
+/// tags it; `version` is the release as the alias names it.
+struct GoTool {
+    build_path: &'static str,
+    name: &'static str,
+    repository: &'static str,
+    tag: &'static str,
+    version: &'static str,
+}
+
+const GO_TOOLS: &[GoTool] = &[
+    GoTool {
+        build_path: "./cmd/golangci-lint",
+        name: "golangci-lint",
+        repository: "golangci/golangci-lint",
+        tag: "v2.5.0",
+        version: "2.5.0",
+    },
+    GoTool {
+        build_path: "./cmd/govulncheck",
+        name: "govulncheck",
+        repository: "golang/vuln",
+        tag: "v1.1.4",
+        version: "1.1.4",
+    },
+    GoTool {
+        build_path: "./cmd/staticcheck",
+        name: "staticcheck",
+        repository: "dominikh/go-tools",
+        tag: "2025.1.1",
+        version: "2025.1.1",
+    },
+];
+
+/// The gates' Go linters and scanners as store binaries on `PATH`. Through
+/// `go tool` they resolve from the module cache, and a sandboxed agent whose
+/// cache is cold DNS-fails on `proxy.golang.org` before the tool ever runs.
+/// Building them here moves every module download to build time.
+pub struct GoTools {
+    name: String,
+    systems: Vec<ArtifactSystem>,
+}
+
+/// Artifact name for one tool. The Go builder names both the source it
+/// unpacks and the binary it writes after the artifact, so the binary comes
+/// out as `bin/{artifact}` and the bundle renames it.
+fn tool_name(user: &str, tool: &GoTool) -> String {
+    format!("{user}-go-tools-{}", tool.name)
+}
+
+/// Directory a GitHub tag archive unpacks into: the repository name and the
+/// tag, less a leading `v` before its version number.
+fn archive_root(tool: &GoTool) -> String {
+    let repository_name = tool
+        .repository
+        .rsplit('/')
+        .next()
+        .unwrap_or(tool.repository);
+
+    let suffix = match tool.tag.strip_prefix('v') {
+        Some(version) if version.starts_with(|c: char| c.is_ascii_digit()) => version,
+        _ => tool.tag,
+    };
+
+    format!("{repository_name}-{suffix}")
+}
+
+fn archive_url(tool: &GoTool) -> String {
+    format!(
+        "https://github.com/{}/archive/refs/tags/{}.tar.gz",
+        tool.repository, tool.tag
+    )
+}
+
+/// Copies each built binary into one `bin/` under its command name.
+fn bundle_script(user: &str, binaries: &[(&GoTool, String)]) -> String {
+    let mut script = String::from("mkdir -p \"$VORPAL_OUTPUT/bin\"\n");
+
+    for (tool, digest) in binaries {
+        script.push_str(&formatdoc! {r#"
+            cp "{artifact}/bin/{binary}" "$VORPAL_OUTPUT/bin/{command}"
+        "#,
+            artifact = get_env_key(digest),
+            binary = tool_name(user, tool),
+            command = tool.name,
+        });
+    }
+
+    script
+}
+
+impl GoTools {
+    pub fn new(name: &str, systems: Vec<ArtifactSystem>) -> Self {
+        Self {
+            name: name.to_string(),
+            systems,
+        }
+    }
+
+    pub async fn build(self, context: &mut impl BuildContext) -> Result<Vec<String>> {
+        let mut binaries = vec![];
+
+        for tool in GO_TOOLS {
+            let build_directory = archive_root(tool);
+
+            let name = tool_name(&self.name, tool);
+
+            let url = archive_url(tool);
+
+            let source = ArtifactSource::new(&name, &url).build();
+
+            let systems = self.systems.clone();
+
+            let go_name = name.clone();
+
+            let artifact = context
+                .external(&name, move |context| {
+                    Box::pin(async move {
+                        Go::new(&go_name, systems)
+                            .with_alias(format!("{}:{}", tool.name, tool.version))
+                            .with_build_directory(&build_directory)
+                            .with_build_path(tool.build_path)
+                            .with_source(source)
+                            .build(context)
+                            .await
+                    })
+                })
+                .await?;
+
+            binaries.push((tool, artifact));
+        }
+
+        let inputs: Vec<String> = binaries.iter().map(|(_, digest)| digest.clone()).collect();
+
+        let step = context
+            .shell(inputs.clone(), vec![], bundle_script(&self.name, &binaries))
+            .await?;
+
+        let name = format!("{}-go-tools", self.name);
+
+        let digest = context
+            .artifact(&name, vec![step], vec![], self.systems)
+            .await?;
+
+        Ok(vec![digest])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{archive_root, archive_url, GoTools, GO_TOOLS};
+    use crate::context::mock::MockContext;
+    use vorpal_sdk::api::artifact::ArtifactSystem::Aarch64Darwin;
+
+    #[test]
+    fn tools_download_the_tag_their_repository_spells() {
+        let tool = |name| GO_TOOLS.iter().find(|tool| tool.name == name).unwrap();
+
+        assert_eq!(
+            archive_url(tool("staticcheck")),
+            "https://github.com/dominikh/go-tools/archive/refs/tags/2025.1.1.tar.gz"
+        );
+        assert_eq!(archive_root(tool("staticcheck")), "go-tools-2025.1.1");
+        assert_eq!(
+            archive_url(tool("golangci-lint")),
+            "https://github.com/golangci/golangci-lint/archive/refs/tags/v2.5.0.tar.gz"
+        );
+        assert_eq!(archive_root(tool("golangci-lint")), "golangci-lint-2.5.0");
+    }
+
+    #[test]
+    fn each_tool_builds_the_command_it_is_named_after() {
+        for tool in GO_TOOLS {
+            assert_eq!(tool.build_path, format!("./cmd/{}", tool.name));
+        }
+    }
+
+    #[tokio::test]
+    async fn binaries_are_bundled_under_their_command_names() {
+        let mut context = MockContext::new(Aarch64Darwin);
+
+        let artifacts = GoTools::new("user", vec![Aarch64Darwin])
+            .build(&mut context)
+            .await
+            .unwrap();
+
+        assert_eq!(artifacts, vec!["user-go-tools".to_string()]);
+        assert_eq!(
+            context.names(),
+            vec![
+                "user-go-tools-golangci-lint",
+                "user-go-tools-govulncheck",
+                "user-go-tools-staticcheck",
+                "user-go-tools",
+            ]
+        );
+        assert!(context.registered("user-go-tools").script().contains(
+            "cp \"$VORPAL_ARTIFACT_user-go-tools-staticcheck/bin/user-go-tools-staticcheck\" \"$VORPAL_OUTPUT/bin/staticcheck\""
+        ));
+    }
+}
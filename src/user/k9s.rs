@@ -1,8 +1,11 @@
-use crate::file::FileCreate;
-use anyhow::Result;
+use crate::{context::BuildContext, file::FileCreate, home::HomePath, install::Install};
+use anyhow::{bail, Result};
 use indoc::formatdoc;
 use vorpal_artifacts::artifact::k9s;
-use vorpal_sdk::{api::artifact::ArtifactSystem, artifact::get_env_key, context::ConfigContext};
+use vorpal_sdk::api::artifact::{
+    ArtifactSystem,
+    ArtifactSystem::{Aarch64Darwin, Aarch64Linux, X8664Darwin, X8664Linux},
+};
 
 struct K9sTheme {
     name: String,
@@ -78,6 +81,21 @@ pub struct K9s {
     systems: Vec<ArtifactSystem>,
 }
 
+/// Where K9s looks for skins on each platform: its config directory follows
+/// `xdg.ConfigHome`, which the xdg library puts under `Application Support`
+/// on macOS.
+fn skin_path(system: ArtifactSystem) -> Result<HomePath> {
+    let skin = "k9s/skins/tokyo_night.yaml";
+
+    match system {
+        Aarch64Darwin | X8664Darwin => {
+            HomePath::new(&format!("Library/Application Support/{skin}"))
+        }
+        Aarch64Linux | X8664Linux => HomePath::new(&format!(".config/{skin}")),
+        _ => bail!("no K9s skin location for {}", system.as_str_name()),
+    }
+}
+
 impl K9sTheme {
     pub fn new(name: &str, systems: Vec<ArtifactSystem>) -> Self {
         Self {
@@ -437,8 +455,9 @@ impl K9sTheme {
         self
     }
 
-    pub async fn build(self, context: &mut ConfigContext) -> Result<String> {
-        let content = formatdoc! {r#"
+    /// The skin YAML k9s reads.
+    pub fn render(&self) -> String {
+        formatdoc! {r#"
             k9s:
               # General K9s styles
               body:
@@ -576,7 +595,11 @@ impl K9sTheme {
             views_logs_bg_color = self.views_logs_bg_color,
             views_logs_indicator_fg_color = self.views_logs_indicator_fg_color,
             views_logs_indicator_bg_color = self.views_logs_indicator_bg_color,
-        };
+        }
+    }
+
+    pub async fn build(self, context: &mut impl BuildContext) -> Result<String> {
+        let content = self.render();
 
         FileCreate::new(&format!("{}-k9s-theme", self.name), self.systems, &content)
             .build(context)
@@ -592,12 +615,8 @@ impl K9s {
         }
     }
 
-    pub async fn build(
-        self,
-        context: &mut ConfigContext,
-    ) -> Result<(Vec<String>, Vec<(String, String)>)> {
-        let mut artifacts = vec![k9s::K9s::new().build(context).await?];
-
+    /// The TokyoNight skin this component installs.
+    fn theme(&self) -> K9sTheme {
         // Define TokyoNight color palette
 
         let theme_background = "default";
@@ -613,7 +632,7 @@ impl K9s {
         let theme_selection = "#44475a";
         let theme_yellow = "#f1fa8c";
 
-        let theme = K9sTheme::new(&self.name, self.systems.clone())
+        K9sTheme::new(&self.name, self.systems.clone())
             .with_body_bg_color(theme_background)
             .with_body_fg_color(theme_foreground)
             .with_body_logo_color(theme_purple)
@@ -678,17 +697,28 @@ impl K9s {
             .with_views_yaml_colon_color(theme_purple)
             .with_views_yaml_key_color(theme_pink)
             .with_views_yaml_value_color(theme_foreground)
-            .build(context)
+    }
+
+    pub async fn build(
+        self,
+        context: &mut impl BuildContext,
+    ) -> Result<(Vec<String>, Vec<Install>)> {
+        let binary = context
+            .external("k9s", |context| Box::pin(k9s::K9s::new().build(context)))
             .await?;
 
-        let symlinks = vec![(
-            format!("{}/{}-k9s-theme", get_env_key(&theme), self.name),
-            "$HOME/Library/Application\\ Support/k9s/skins/tokyo_night.yaml".to_string(),
+        let mut artifacts = vec![binary];
+
+        let theme = self.theme().build(context).await?;
+
+        let installs = vec![Install::link(
+            FileCreate::output_file(&theme, &format!("{}-k9s-theme", self.name)),
+            skin_path(context.get_system())?,
         )];
 
         artifacts.push(theme);
 
-        Ok((artifacts, symlinks))
+        Ok((artifacts, installs))
     }
 }
 
@@ -699,3 +729,27 @@ fn format_yaml_list(colors: &[String]) -> String {
         .collect::<Vec<_>>()
         .join("")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{skin_path, K9s};
+    use crate::snapshot::assert_snapshot;
+    use vorpal_sdk::api::artifact::ArtifactSystem::{Aarch64Darwin, X8664Linux};
+
+    #[test]
+    fn the_skin_installs_where_k9s_looks_on_each_platform() {
+        assert_eq!(
+            skin_path(Aarch64Darwin).unwrap().tilde(),
+            "~/Library/Application Support/k9s/skins/tokyo_night.yaml"
+        );
+        assert_eq!(
+            skin_path(X8664Linux).unwrap().tilde(),
+            "~/.config/k9s/skins/tokyo_night.yaml"
+        );
+    }
+
+    #[test]
+    fn the_skin_matches_its_snapshot() {
+        assert_snapshot("k9s-skin.yaml", &K9s::new("user", vec![]).theme().render());
+    }
+}
@@ -1,4 +1,16 @@
-use crate::file::FileCreate;
+use crate::{
+    context::BuildContext,
+    environment::{Environment, Value},
+    health::{Check, Probe},
+    home::HomePath,
+    install::Install,
+    store::StorePath,
+    user::neovim::{
+        ftplugin::{FiletypeSettings, Ftplugin, Indent},
+        parser::TreeSitterParser,
+        plugin::{plugin_home, NeovimPlugin, PluginLock},
+    },
+};
 use anyhow::Result;
 use vorpal_artifacts::artifact::{
     bash_language_server::BashLanguageServer, cue::Cue, lua_language_server::LuaLanguageServer,
@@ -7,11 +19,27 @@ use vorpal_artifacts::artifact::{
     vscode_langservers_extracted::VscodeLangserversExtracted,
     yaml_language_server::YamlLanguageServer,
 };
-use vorpal_sdk::{
-    api::artifact::ArtifactSystem,
-    artifact::{get_env_key, gopls::Gopls},
-    context::ConfigContext,
-};
+use vorpal_sdk::{api::artifact::ArtifactSystem, artifact::gopls::Gopls};
+
+mod ftplugin;
+mod parser;
+mod plugin;
+
+/// Grammars compiled at build time, so no parser is ever compiled on first
+/// use — which fails in a sandboxed session and on an offline host. Lua and
+/// markdown are left out: Neovim bundles them.
+const TREE_SITTER_GRAMMARS: &[&str] = &["bash", "go", "rust", "toml", "yaml"];
+
+/// The executables the language server artifacts put on `PATH`.
+const LANGUAGE_SERVER_COMMANDS: &[&str] = &[
+    "bash-language-server",
+    "cue",
+    "gopls",
+    "lua-language-server",
+    "typescript-language-server",
+    "vscode-json-language-server",
+    "yaml-language-server",
+];
 
 pub struct Neovim {
     name: String,
@@ -26,51 +54,173 @@ impl Neovim {
         }
     }
 
+    pub fn environment() -> Environment {
+        Environment::new().with_var("EDITOR", Value::literal("nvim"))
+    }
+
+    fn ftplugin(&self) -> Ftplugin {
+        Ftplugin::new(&self.name, self.systems.clone())
+            .with_filetype(
+                "go",
+                FiletypeSettings::new()
+                    .with_formatprg("gofmt")
+                    .with_indent(Indent::Tabs(4)),
+            )
+            .with_filetype(
+                "markdown",
+                FiletypeSettings::new()
+                    .with_spell(true)
+                    .with_textwidth(80)
+                    .with_wrap(true),
+            )
+            .with_filetype(
+                "toml",
+                FiletypeSettings::new().with_indent(Indent::Spaces(2)),
+            )
+    }
+
+    /// The editor has to be on `PATH`; a missing language server only costs
+    /// its filetype completion and diagnostics.
+    pub fn checks() -> Vec<Check> {
+        let remediation = "run `just activate` and start a new shell";
+
+        std::iter::once(Check::fail(
+            "nvim is on PATH",
+            Probe::OnPath("nvim".to_string()),
+            remediation,
+        ))
+        .chain(LANGUAGE_SERVER_COMMANDS.iter().map(|command| {
+            Check::warn(
+                &format!("{command} is on PATH"),
+                Probe::OnPath(command.to_string()),
+                remediation,
+            )
+        }))
+        .collect()
+    }
+
     pub async fn build(
         self,
-        context: &mut ConfigContext,
-    ) -> Result<(Vec<String>, Vec<(String, String)>)> {
-        let binaries = vec![neovim::Neovim::new().build(context).await?];
+        context: &mut impl BuildContext,
+    ) -> Result<(Vec<String>, Vec<Install>)> {
+        let binaries = vec![
+            context
+                .external("neovim", |context| {
+                    Box::pin(neovim::Neovim::new().build(context))
+                })
+                .await?,
+        ];
+
+        let tree_sitter = context
+            .external("tree-sitter", |context| {
+                Box::pin(TreeSitter::new().build(context))
+            })
+            .await?;
+
+        let parsers = TREE_SITTER_GRAMMARS
+            .iter()
+            .fold(
+                TreeSitterParser::new(&self.name, &tree_sitter, self.systems.clone()),
+                |parser, grammar| parser.with_grammar(grammar),
+            )
+            .build(context)
+            .await?;
 
         let language_servers = vec![
-            BashLanguageServer::new().build(context).await?,
-            Cue::new().build(context).await?,
-            Gopls::new().build(context).await?,
-            LuaLanguageServer::new().build(context).await?,
-            TreeSitter::new().build(context).await?,
-            Typescript::new().build(context).await?,
-            TypescriptLanguageServer::new().build(context).await?,
-            VscodeLangserversExtracted::new().build(context).await?,
-            YamlLanguageServer::new().build(context).await?,
+            context
+                .external("bash-language-server", |context| {
+                    Box::pin(BashLanguageServer::new().build(context))
+                })
+                .await?,
+            context
+                .external("cue", |context| Box::pin(Cue::new().build(context)))
+                .await?,
+            context
+                .external("gopls", |context| Box::pin(Gopls::new().build(context)))
+                .await?,
+            context
+                .external("lua-language-server", |context| {
+                    Box::pin(LuaLanguageServer::new().build(context))
+                })
+                .await?,
+            tree_sitter,
+            context
+                .external("typescript", |context| {
+                    Box::pin(Typescript::new().build(context))
+                })
+                .await?,
+            context
+                .external("typescript-language-server", |context| {
+                    Box::pin(TypescriptLanguageServer::new().build(context))
+                })
+                .await?,
+            context
+                .external("vscode-langservers-extracted", |context| {
+                    Box::pin(VscodeLangserversExtracted::new().build(context))
+                })
+                .await?,
+            context
+                .external("yaml-language-server", |context| {
+                    Box::pin(YamlLanguageServer::new().build(context))
+                })
+                .await?,
         ];
 
-        let mut ftplugins = vec![];
+        let mut plugins = vec![];
+        let mut plugin_installs = vec![];
 
-        let markdown_vim = FileCreate::new(
-            &format!("{}-neovim-ftplugin-markdown-vim", self.name),
-            self.systems.clone(),
-            "setlocal wrap",
-        )
-        .build(context)
-        .await?;
+        for locked in PluginLock::load()?.plugin {
+            let plugin = NeovimPlugin::from_lock(&self.name, &locked, self.systems.clone());
+            let artifact = plugin.build(context).await?;
 
-        ftplugins.push(markdown_vim.clone());
+            plugin_installs.push(Install::link(
+                StorePath::output(&artifact).join(&plugin.path()),
+                plugin_home(&locked.name)?,
+            ));
+
+            plugins.push(artifact);
+        }
+
+        let ftplugin = self.ftplugin().build(context).await?;
 
         let artifacts = binaries
             .into_iter()
-            .chain(ftplugins)
+            .chain(std::iter::once(ftplugin.clone()))
             .chain(language_servers)
+            .chain(plugins)
+            .chain(std::iter::once(parsers.clone()))
             .collect();
 
-        let symlinks = vec![(
-            format!(
-                "{}/{}-neovim-ftplugin-markdown-vim",
-                get_env_key(&markdown_vim),
-                self.name
-            ),
-            "${HOME}/.config/nvim/after/ftplugin/markdown.vim".to_string(),
+        let mut installs = vec![Install::link(
+            StorePath::output(&ftplugin),
+            HomePath::new(".config/nvim/after/ftplugin")?,
         )];
 
-        Ok((artifacts, symlinks))
+        installs.extend(plugin_installs);
+
+        installs.push(Install::link(
+            StorePath::output(&parsers).join("parser"),
+            HomePath::new(".local/share/nvim/site/parser")?,
+        ));
+
+        installs.push(Install::link(
+            StorePath::output(&parsers).join("queries"),
+            HomePath::new(".local/share/nvim/site/queries")?,
+        ));
+
+        Ok((artifacts, installs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Neovim;
+    use crate::snapshot::assert_snapshot;
+
+    #[test]
+    fn ftplugin_files_match_their_snapshots() {
+        for (file, contents) in Neovim::new("user", vec![]).ftplugin().files() {
+            assert_snapshot(&format!("neovim-ftplugin-{file}"), &contents);
+        }
     }
 }
@@ -0,0 +1,225 @@
+use crate::context::BuildContext;
+use anyhow::Result;
+use indoc::formatdoc;
+use std::collections::BTreeMap;
+use vorpal_sdk::api::artifact::ArtifactSystem;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Indent {
+    Spaces(u8),
+    Tabs(u8),
+}
+
+/// Buffer-local options for one filetype. Anything left unset keeps Neovim's
+/// own default for that filetype.
+#[derive(Clone, Debug, Default)]
+pub struct FiletypeSettings {
+    formatprg: Option<String>,
+    indent: Option<Indent>,
+    spell: Option<bool>,
+    textwidth: Option<u16>,
+    wrap: Option<bool>,
+}
+
+pub struct Ftplugin {
+    filetypes: BTreeMap<String, FiletypeSettings>,
+    name: String,
+    systems: Vec<ArtifactSystem>,
+}
+
+fn toggle(option: &str, enabled: bool) -> String {
+    if enabled {
+        format!("setlocal {option}")
+    } else {
+        format!("setlocal no{option}")
+    }
+}
+
+/// Escapes an option value for `:setlocal`, where a space ends the value, `|`
+/// ends the command and `"` starts a comment.
+fn escape_option_value(value: &str) -> String {
+    value
+        .chars()
+        .flat_map(|c| match c {
+            ' ' | '\\' | '|' | '"' => vec!['\\', c],
+            _ => vec![c],
+        })
+        .collect()
+}
+
+impl FiletypeSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_formatprg(mut self, command: &str) -> Self {
+        self.formatprg = Some(command.to_string());
+        self
+    }
+
+    pub fn with_indent(mut self, indent: Indent) -> Self {
+        self.indent = Some(indent);
+        self
+    }
+
+    pub fn with_spell(mut self, spell: bool) -> Self {
+        self.spell = Some(spell);
+        self
+    }
+
+    pub fn with_textwidth(mut self, width: u16) -> Self {
+        self.textwidth = Some(width);
+        self
+    }
+
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = Some(wrap);
+        self
+    }
+
+    /// The ftplugin body: one `setlocal` per line, in a fixed order so the
+    /// artifact does not change when settings are declared differently.
+    pub fn render(&self) -> String {
+        let mut lines = vec![];
+
+        if let Some(formatprg) = &self.formatprg {
+            lines.push(format!(
+                "setlocal formatprg={}",
+                escape_option_value(formatprg)
+            ));
+        }
+
+        match self.indent {
+            Some(Indent::Spaces(width)) => lines.push(format!(
+                "setlocal expandtab shiftwidth={width} softtabstop={width}"
+            )),
+            Some(Indent::Tabs(width)) => lines.push(format!(
+                "setlocal noexpandtab shiftwidth={width} tabstop={width}"
+            )),
+            None => {}
+        }
+
+        if let Some(spell) = self.spell {
+            lines.push(toggle("spell", spell));
+        }
+
+        if let Some(textwidth) = self.textwidth {
+            lines.push(format!("setlocal textwidth={textwidth}"));
+        }
+
+        if let Some(wrap) = self.wrap {
+            lines.push(toggle("wrap", wrap));
+        }
+
+        lines.join("\n")
+    }
+}
+
+impl Ftplugin {
+    pub fn new(name: &str, systems: Vec<ArtifactSystem>) -> Self {
+        Self {
+            filetypes: BTreeMap::new(),
+            name: name.to_string(),
+            systems,
+        }
+    }
+
+    pub fn with_filetype(mut self, filetype: &str, settings: FiletypeSettings) -> Self {
+        self.filetypes.insert(filetype.to_string(), settings);
+        self
+    }
+
+    /// `{filetype}.vim` and its body for every declared filetype, in
+    /// filetype order.
+    pub fn files(&self) -> Vec<(String, String)> {
+        self.filetypes
+            .iter()
+            .map(|(filetype, settings)| (format!("{filetype}.vim"), settings.render()))
+            .collect()
+    }
+
+    /// One artifact holding `{filetype}.vim` for every declared filetype,
+    /// linked as Neovim's whole `after/ftplugin` directory.
+    pub async fn build(self, context: &mut impl BuildContext) -> Result<String> {
+        let step_script = self
+            .files()
+            .into_iter()
+            .map(|(file, contents)| {
+                formatdoc! {"
+                    cat << 'EOF' > $VORPAL_OUTPUT/{file}
+                    {contents}
+                    EOF
+                "}
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let step = context.shell(vec![], vec![], step_script).await?;
+
+        let name = format!("{}-neovim-ftplugin", self.name);
+
+        let digest = context
+            .artifact(&name, vec![step], vec![], self.systems)
+            .await?;
+
+        Ok(digest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FiletypeSettings, Indent};
+
+    #[test]
+    fn a_single_setting_renders_a_single_line() {
+        assert_eq!(
+            FiletypeSettings::new().with_wrap(true).render(),
+            "setlocal wrap"
+        );
+    }
+
+    #[test]
+    fn disabled_toggles_render_their_no_form() {
+        assert_eq!(
+            FiletypeSettings::new().with_spell(false).render(),
+            "setlocal nospell"
+        );
+    }
+
+    #[test]
+    fn settings_render_in_a_fixed_order() {
+        let settings = FiletypeSettings::new()
+            .with_wrap(false)
+            .with_textwidth(100)
+            .with_indent(Indent::Tabs(4))
+            .with_formatprg("gofmt");
+
+        assert_eq!(
+            settings.render(),
+            "setlocal formatprg=gofmt\n\
+             setlocal noexpandtab shiftwidth=4 tabstop=4\n\
+             setlocal textwidth=100\n\
+             setlocal nowrap"
+        );
+    }
+
+    #[test]
+    fn space_indents_expand_tabs() {
+        assert_eq!(
+            FiletypeSettings::new()
+                .with_indent(Indent::Spaces(2))
+                .render(),
+            "setlocal expandtab shiftwidth=2 softtabstop=2"
+        );
+    }
+
+    #[test]
+    fn formatprg_arguments_are_escaped_for_setlocal() {
+        assert_eq!(
+            FiletypeSettings::new()
+                .with_formatprg("taplo fmt -")
+                .render(),
+            "setlocal formatprg=taplo\\ fmt\\ -"
+        );
+    }
+}
@@ -0,0 +1,260 @@
+use crate::context::BuildContext;
+use anyhow::{anyhow, bail, Result};
+use indoc::{formatdoc, indoc};
+use vorpal_sdk::{
+    api::artifact::ArtifactSystem,
+    artifact::{get_env_key, ArtifactSource},
+};
+
+/// Grammars Neovim ships compiled, each with queries written for its own
+/// parser. A second copy earlier on the runtime path would shadow both.
+const NEOVIM_BUNDLED: &[&str] = &[
+    "c",
+    "lua",
+    "markdown",
+    "markdown_inline",
+    "query",
+    "vim",
+    "vimdoc",
+];
+
+/// One grammar repository at a release tag, holding a single parser at the
+/// archive root. `digest` is the sha256 of the archive, so a tag moved since
+/// fails the fetch; a grammar without one is refused rather than fetched
+/// unverified.
+struct Grammar {
+    digest: Option<&'static str>,
+    name: &'static str,
+    repository: &'static str,
+    version: &'static str,
+}
+
+const GRAMMARS: &[Grammar] = &[
+    Grammar {
+        digest: None,
+        name: "bash",
+        repository: "tree-sitter/tree-sitter-bash",
+        version: "0.23.3",
+    },
+    Grammar {
+        digest: None,
+        name: "go",
+        repository: "tree-sitter/tree-sitter-go",
+        version: "0.23.4",
+    },
+    Grammar {
+        digest: None,
+        name: "rust",
+        repository: "tree-sitter/tree-sitter-rust",
+        version: "0.23.2",
+    },
+    Grammar {
+        digest: None,
+        name: "toml",
+        repository: "tree-sitter-grammars/tree-sitter-toml",
+        version: "0.7.0",
+    },
+    Grammar {
+        digest: None,
+        name: "yaml",
+        repository: "tree-sitter-grammars/tree-sitter-yaml",
+        version: "0.7.0",
+    },
+];
+
+pub struct TreeSitterParser {
+    grammars: Vec<String>,
+    name: String,
+    systems: Vec<ArtifactSystem>,
+    tree_sitter: String,
+}
+
+fn grammar(name: &str) -> Result<&'static Grammar> {
+    if NEOVIM_BUNDLED.contains(&name) {
+        bail!("Neovim bundles the {name} tree-sitter parser and its queries");
+    }
+
+    GRAMMARS
+        .iter()
+        .find(|grammar| grammar.name == name)
+        .ok_or_else(|| anyhow!("no tree-sitter grammar is pinned for {name}"))
+}
+
+/// The archive digest the grammar's source is verified against.
+fn digest(grammar: &Grammar) -> Result<&'static str> {
+    grammar.digest.ok_or_else(|| {
+        anyhow!(
+            "tree-sitter grammar {} v{} has no digest pinned; record its archive sha256 in GRAMMARS",
+            grammar.name,
+            grammar.version
+        )
+    })
+}
+
+fn archive_url(grammar: &Grammar) -> String {
+    format!(
+        "https://github.com/{}/archive/refs/tags/v{}.tar.gz",
+        grammar.repository, grammar.version
+    )
+}
+
+fn source_name(name: &str, grammar: &Grammar) -> String {
+    format!("{name}-tree-sitter-{}", grammar.name)
+}
+
+/// Directory a GitHub tag archive unpacks into: the repository name and the
+/// tag without its leading `v`.
+fn archive_root(grammar: &Grammar) -> String {
+    let repository_name = grammar
+        .repository
+        .rsplit('/')
+        .next()
+        .unwrap_or(grammar.repository);
+
+    format!("{repository_name}-{}", grammar.version)
+}
+
+/// Compiles every grammar into `parser/{lang}.so` and copies its queries to
+/// `queries/{lang}/` — the two directories Neovim looks for on its runtime
+/// path. `tree-sitter build` compiles with the host's C compiler, so the step
+/// stops first with a readable error when there is none.
+fn build_script(name: &str, tree_sitter: &str, grammars: &[&Grammar]) -> String {
+    let mut script = String::from(indoc! {r#"
+        if ! command -v "${CC:-cc}" >/dev/null; then
+            echo "tree-sitter parsers need a C compiler: install one or set CC" >&2
+            exit 1
+        fi
+
+        mkdir -p "$VORPAL_OUTPUT/parser" "$VORPAL_OUTPUT/queries"
+    "#});
+
+    for grammar in grammars {
+        script.push_str(&formatdoc! {r#"
+
+            pushd "source/{source}/{root}"
+            "{tree_sitter}/bin/tree-sitter" build --output "$VORPAL_OUTPUT/parser/{parser}.so" .
+            if [ -d queries ]; then
+                mkdir -p "$VORPAL_OUTPUT/queries/{parser}"
+                cp -r queries/. "$VORPAL_OUTPUT/queries/{parser}"
+            fi
+            popd
+        "#,
+            parser = grammar.name,
+            root = archive_root(grammar),
+            source = source_name(name, grammar),
+            tree_sitter = tree_sitter,
+        });
+    }
+
+    script
+}
+
+impl TreeSitterParser {
+    pub fn new(name: &str, tree_sitter: &str, systems: Vec<ArtifactSystem>) -> Self {
+        Self {
+            grammars: vec![],
+            name: name.to_string(),
+            systems,
+            tree_sitter: tree_sitter.to_string(),
+        }
+    }
+
+    pub fn with_grammar(mut self, grammar: &str) -> Self {
+        self.grammars.push(grammar.to_string());
+        self
+    }
+
+    pub async fn build(self, context: &mut impl BuildContext) -> Result<String> {
+        let grammars = self
+            .grammars
+            .iter()
+            .map(|name| grammar(name))
+            .collect::<Result<Vec<_>>>()?;
+
+        let urls: Vec<String> = grammars
+            .iter()
+            .map(|grammar| archive_url(grammar))
+            .collect();
+
+        let sources = grammars
+            .iter()
+            .zip(&urls)
+            .map(|(grammar, url)| {
+                Ok(ArtifactSource::new(&source_name(&self.name, grammar), url)
+                    .with_digest(digest(grammar)?)
+                    .build())
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let step_script = build_script(&self.name, &get_env_key(&self.tree_sitter), &grammars);
+
+        let step = context
+            .shell(vec![self.tree_sitter.clone()], vec![], step_script)
+            .await?;
+
+        let name = format!("{}-tree-sitter-parsers", self.name);
+
+        let digest = context
+            .artifact(&name, vec![step], sources, self.systems)
+            .await?;
+
+        Ok(digest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{archive_root, build_script, digest, grammar, Grammar};
+
+    const GRAMMAR: Grammar = Grammar {
+        digest: None,
+        name: "go",
+        repository: "tree-sitter/tree-sitter-go",
+        version: "0.23.4",
+    };
+
+    #[test]
+    fn an_unpinned_grammar_is_an_error() {
+        assert!(grammar("cobol").is_err());
+    }
+
+    #[test]
+    fn a_grammar_without_a_digest_is_an_error() {
+        let error = digest(&GRAMMAR).err().unwrap().to_string();
+
+        assert!(error.contains("go v0.23.4 has no digest"), "{error}");
+        assert_eq!(
+            digest(&Grammar {
+                digest: Some("abc123"),
+                ..GRAMMAR
+            })
+            .unwrap(),
+            "abc123"
+        );
+    }
+
+    #[test]
+    fn tag_archives_unpack_without_the_leading_v() {
+        assert_eq!(archive_root(&GRAMMAR), "tree-sitter-go-0.23.4");
+    }
+
+    #[test]
+    fn grammars_neovim_bundles_are_not_compiled_again() {
+        for name in ["lua", "markdown", "vimdoc"] {
+            let error = grammar(name).err().unwrap().to_string();
+
+            assert!(error.contains("Neovim bundles"), "{name}: {error}");
+        }
+    }
+
+    #[test]
+    fn parsers_compile_with_the_tree_sitter_artifact() {
+        let script = build_script("user", "/store/ts", &[&GRAMMAR]);
+
+        assert!(script.contains(
+            "\"/store/ts/bin/tree-sitter\" build --output \"$VORPAL_OUTPUT/parser/go.so\" ."
+        ));
+        assert!(script.contains("cp -r queries/. \"$VORPAL_OUTPUT/queries/go\""));
+        assert!(script.starts_with("if ! command -v \"${CC:-cc}\" >/dev/null; then"));
+    }
+}
@@ -0,0 +1,222 @@
+use crate::{context::BuildContext, file::FileSource, home::HomePath};
+use anyhow::{bail, Result};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use vorpal_sdk::api::artifact::ArtifactSystem;
+
+const PLUGINS_LOCK: &str = include_str!("nvim-plugins.lock");
+
+/// One plugin as recorded in `nvim-plugins.lock`: a commit and the digest
+/// of its archive.
+#[derive(Debug, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct LockedPlugin {
+    pub digest: String,
+    pub name: String,
+    pub repository: String,
+    pub rev: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PluginLock {
+    #[serde(default)]
+    pub plugin: Vec<LockedPlugin>,
+}
+
+pub struct NeovimPlugin {
+    digest: String,
+    name: String,
+    plugin: String,
+    repository: String,
+    rev: String,
+    systems: Vec<ArtifactSystem>,
+}
+
+fn is_commit_sha(rev: &str) -> bool {
+    rev.len() == 40 && rev.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f'))
+}
+
+/// GitHub's archive of one commit. Vorpal unpacks it on fetch.
+fn archive_url(repository: &str, rev: &str) -> String {
+    format!("https://github.com/{repository}/archive/{rev}.tar.gz")
+}
+
+/// Directory the archive unpacks into: GitHub names it after the repository,
+/// not the owner, suffixed with the full commit sha.
+fn archive_root(repository: &str, rev: &str) -> String {
+    let repository_name = repository.rsplit('/').next().unwrap_or(repository);
+
+    format!("{repository_name}-{rev}")
+}
+
+/// Install destination inside Neovim's own package path, where every
+/// `start/` entry is loaded at startup without a plugin manager.
+pub fn plugin_home(plugin: &str) -> Result<HomePath> {
+    HomePath::new(&format!(
+        ".local/share/nvim/site/pack/vorpal/start/{plugin}"
+    ))
+}
+
+impl PluginLock {
+    pub fn load() -> Result<Self> {
+        Self::parse(PLUGINS_LOCK)
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let lock: Self = toml::from_str(content)?;
+
+        let mut names = BTreeSet::new();
+
+        for plugin in &lock.plugin {
+            if !names.insert(plugin.name.as_str()) {
+                bail!("nvim-plugins.lock pins {} more than once", plugin.name);
+            }
+
+            if plugin.repository.split('/').count() != 2 {
+                bail!(
+                    "nvim-plugins.lock: {} repository {} is not owner/name",
+                    plugin.name,
+                    plugin.repository
+                );
+            }
+
+            if !is_commit_sha(&plugin.rev) {
+                bail!(
+                    "nvim-plugins.lock: {} rev {} is not a full commit sha",
+                    plugin.name,
+                    plugin.rev
+                );
+            }
+
+            if plugin.digest.trim().is_empty() {
+                bail!("nvim-plugins.lock: {} digest is empty", plugin.name);
+            }
+        }
+
+        Ok(lock)
+    }
+}
+
+impl NeovimPlugin {
+    /// `locked` as [`PluginLock::parse`] accepted it.
+    pub fn from_lock(name: &str, locked: &LockedPlugin, systems: Vec<ArtifactSystem>) -> Self {
+        Self {
+            digest: locked.digest.clone(),
+            name: name.to_string(),
+            plugin: locked.name.clone(),
+            repository: locked.repository.clone(),
+            rev: locked.rev.clone(),
+            systems,
+        }
+    }
+
+    /// Path of the plugin tree inside the built artifact.
+    pub fn path(&self) -> String {
+        archive_root(&self.repository, &self.rev)
+    }
+
+    pub async fn build(&self, context: &mut impl BuildContext) -> Result<String> {
+        FileSource::new(
+            &format!("{}-neovim-plugin-{}", self.name, self.plugin),
+            &archive_url(&self.repository, &self.rev),
+            self.systems.clone(),
+        )
+        .with_digest(&self.digest)
+        .build(context)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{archive_root, archive_url, plugin_home, PluginLock};
+
+    const REV: &str = "0123456789abcdef0123456789abcdef01234567";
+
+    fn entry(fields: &str) -> String {
+        format!("[[plugin]]\nname = \"a\"\nrepository = \"o/a\"\n{fields}")
+    }
+
+    #[test]
+    fn shipped_lock_parses() {
+        let lock = PluginLock::load().unwrap();
+
+        assert!(lock.plugin.iter().all(|plugin| !plugin.digest.is_empty()));
+    }
+
+    #[test]
+    fn locked_plugins_parse_with_their_rev_and_digest() {
+        let lock = PluginLock::parse(&format!(
+            r#"
+            [[plugin]]
+            name = "tokyonight.nvim"
+            repository = "folke/tokyonight.nvim"
+            rev = "{REV}"
+            digest = "abc123"
+            "#
+        ))
+        .unwrap();
+
+        assert_eq!(lock.plugin.len(), 1);
+        assert_eq!(lock.plugin[0].rev, REV);
+        assert_eq!(lock.plugin[0].digest, "abc123");
+    }
+
+    #[test]
+    fn a_plugin_needs_a_rev_and_a_digest() {
+        let rev = format!("rev = \"{REV}\"\n");
+        let digest = "digest = \"abc123\"\n";
+
+        assert!(PluginLock::parse(&entry("")).is_err());
+        assert!(PluginLock::parse(&entry(&rev)).is_err());
+        assert!(PluginLock::parse(&entry(digest)).is_err());
+        assert!(PluginLock::parse(&entry(&format!("{rev}digest = \" \"\n"))).is_err());
+        assert!(PluginLock::parse(&entry(&format!("{rev}{digest}"))).is_ok());
+    }
+
+    #[test]
+    fn a_branch_or_tag_is_not_a_rev() {
+        for rev in ["main", "v4.14.1", &REV[..12]] {
+            let lock = entry(&format!("rev = \"{rev}\"\ndigest = \"abc123\"\n"));
+
+            assert!(PluginLock::parse(&lock).is_err(), "{rev} was accepted");
+        }
+    }
+
+    #[test]
+    fn a_plugin_pinned_twice_is_rejected() {
+        let lock = entry(&format!("rev = \"{REV}\"\ndigest = \"abc123\"\n"));
+
+        assert!(PluginLock::parse(&lock.repeat(2)).is_err());
+    }
+
+    #[test]
+    fn unknown_lock_keys_are_rejected() {
+        let lock = entry(&format!(
+            "rev = \"{REV}\"\ndigest = \"abc123\"\ntag = \"v4.14.1\"\n"
+        ));
+
+        assert!(PluginLock::parse(&lock).is_err());
+    }
+
+    #[test]
+    fn archives_unpack_under_the_repository_name_and_commit() {
+        assert_eq!(
+            archive_url("folke/tokyonight.nvim", REV),
+            format!("https://github.com/folke/tokyonight.nvim/archive/{REV}.tar.gz")
+        );
+        assert_eq!(
+            archive_root("folke/tokyonight.nvim", REV),
+            format!("tokyonight.nvim-{REV}")
+        );
+    }
+
+    #[test]
+    fn plugins_install_into_the_vorpal_start_package() {
+        assert_eq!(
+            plugin_home("tokyonight.nvim").unwrap().tilde(),
+            "~/.local/share/nvim/site/pack/vorpal/start/tokyonight.nvim"
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use crate::environment::PathEntry;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::{env, fs, path::PathBuf};
+
+/// Where a machine keeps its own overlay. It is never committed.
+const LOCAL_OVERLAY_PATH: &str = ".config/dotfiles/overlay.toml";
+
+#[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ClaudeCodeOverlay {
+    /// Org roots sandboxed agents may write under, replacing the default.
+    pub sandbox_bare_repo_roots: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct GhosttyOverlay {
+    pub font_family: Option<String>,
+    pub font_size: Option<u8>,
+}
+
+/// Per-machine differences applied to the shared configuration before it is
+/// built. Every field is optional; an empty overlay changes nothing.
+#[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Overlay {
+    #[serde(default)]
+    pub claude_code: ClaudeCodeOverlay,
+    #[serde(default)]
+    pub ghostty: GhosttyOverlay,
+    /// Extra `PATH` directories, absolute or `~/`-relative, after the
+    /// shared ones.
+    #[serde(default)]
+    pub path: Vec<String>,
+}
+
+impl Overlay {
+    pub fn parse(content: &str) -> Result<Self> {
+        let overlay: Self = toml::from_str(content)?;
+
+        for entry in &overlay.path {
+            Self::path_entry(entry)?;
+        }
+
+        Ok(overlay)
+    }
+
+    /// The overlay for the machine running the build: its local overlay
+    /// file, or nothing.
+    pub fn detect() -> Result<Self> {
+        let Some(home) = env::var_os("HOME") else {
+            return Ok(Self::default());
+        };
+
+        let path = PathBuf::from(home).join(LOCAL_OVERLAY_PATH);
+
+        match fs::read_to_string(&path) {
+            Ok(content) => Self::parse(&content).context(LOCAL_OVERLAY_PATH),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error).with_context(|| path.display().to_string()),
+        }
+    }
+
+    fn path_entry(entry: &str) -> Result<PathEntry> {
+        if let Some(relative) = entry.strip_prefix("~/") {
+            return Ok(PathEntry::home(relative));
+        }
+
+        if entry.starts_with('/') {
+            return Ok(PathEntry::absolute(entry));
+        }
+
+        bail!("overlay PATH entry {entry} is neither absolute nor ~/-relative")
+    }
+
+    pub fn path_entries(&self) -> Result<Vec<PathEntry>> {
+        self.path
+            .iter()
+            .map(|entry| Self::path_entry(entry))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Overlay;
+    use crate::environment::PathEntry;
+
+    #[test]
+    fn an_empty_overlay_changes_nothing() {
+        assert_eq!(Overlay::parse("").unwrap(), Overlay::default());
+    }
+
+    #[test]
+    fn overlays_set_typed_overrides() {
+        let overlay = Overlay::parse(
+            r#"
+            path = ["~/bin", "/opt/homebrew/bin"]
+
+            [claude_code]
+            sandbox_bare_repo_roots = ["~/src/github.com/example"]
+
+            [ghostty]
+            font_size = 13
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(overlay.ghostty.font_size, Some(13));
+        assert_eq!(
+            overlay.claude_code.sandbox_bare_repo_roots,
+            Some(vec!["~/src/github.com/example".to_string()])
+        );
+        assert_eq!(
+            overlay.path_entries().unwrap(),
+            vec![
+                PathEntry::home("bin"),
+                PathEntry::absolute("/opt/homebrew/bin")
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert!(Overlay::parse("[ghostty]\nfont = 13").is_err());
+    }
+
+    #[test]
+    fn relative_path_entries_are_rejected() {
+        assert!(Overlay::parse("path = [\"bin\"]").is_err());
+    }
+}
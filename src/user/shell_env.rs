@@ -0,0 +1,91 @@
+use crate::{
+    context::BuildContext,
+    environment::{Environment, SHELLS},
+    file::FileCreate,
+    home::HomePath,
+    install::Install,
+};
+use anyhow::Result;
+use vorpal_sdk::api::artifact::ArtifactSystem;
+
+/// The environment as one script per shell under `~/.config/dotfiles/`, for
+/// shells that do not source `vorpal-activate-shell` from `.zprofile`. A
+/// bash user adds `source ~/.config/dotfiles/env.bash` to `.bashrc`, a fish
+/// user `source ~/.config/dotfiles/env.fish` to `config.fish`.
+pub struct ShellEnv {
+    environment: Environment,
+    name: String,
+    systems: Vec<ArtifactSystem>,
+}
+
+impl ShellEnv {
+    pub fn new(name: &str, systems: Vec<ArtifactSystem>, environment: Environment) -> Self {
+        Self {
+            environment,
+            name: name.to_string(),
+            systems,
+        }
+    }
+
+    pub async fn build(
+        self,
+        context: &mut impl BuildContext,
+    ) -> Result<(Vec<String>, Vec<Install>)> {
+        let system = context.get_system();
+
+        let mut artifacts = vec![];
+        let mut installs = vec![];
+
+        for shell in SHELLS {
+            let name = format!("{}-env-{}", self.name, shell.extension());
+
+            let content = self.environment.script(shell, system)?;
+
+            let env = FileCreate::new(&name, self.systems.clone(), &content)
+                .build(context)
+                .await?;
+
+            artifacts.push(env.clone());
+
+            installs.push(Install::link(
+                FileCreate::output_file(&env, &name),
+                HomePath::new(&format!(".config/dotfiles/env.{}", shell.extension()))?,
+            ));
+        }
+
+        Ok((artifacts, installs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        environment::{Environment, PathEntry, Value, SHELLS},
+        snapshot::assert_snapshot,
+    };
+    use vorpal_sdk::api::artifact::ArtifactSystem::Aarch64Darwin;
+
+    #[test]
+    fn env_files_match_their_snapshots() {
+        let environment = Environment::new()
+            .with_path_on(
+                PathEntry::absolute("/Applications/VMware Fusion.app/Contents/Library"),
+                &[Aarch64Darwin],
+            )
+            .with_var("EDITOR", Value::literal("nvim"))
+            .with_var(
+                "GOPATH",
+                Value::Path(PathEntry::home("Development/language/go")),
+            )
+            .with_path(PathEntry::home("Development/language/go/bin"))
+            .with_path(PathEntry::home(".vorpal/bin"))
+            .with_path(PathEntry::home(".local/bin"));
+
+        for shell in SHELLS {
+            assert_snapshot(
+                &format!("env.{}", shell.extension()),
+                &environment.script(shell, Aarch64Darwin).unwrap(),
+            );
+        }
+    }
+}
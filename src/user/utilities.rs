@@ -1,4 +1,6 @@
+use crate::{context::BuildContext, SYSTEMS};
 use anyhow::Result;
+use serde::Deserialize;
 use vorpal_artifacts::artifact::{
     awscli2::Awscli2, delta::Delta, direnv::Direnv, doppler::Doppler, fd::Fd, fzf::Fzf, gum::Gum,
     herdr::Herdr, hunk::Hunk, jj::Jj, jq::Jq, just::Just, kubectl::Kubectl, lazygit::Lazygit,
@@ -6,49 +8,446 @@ use vorpal_artifacts::artifact::{
     terraform::Terraform, tmux::Tmux, zoxide::Zoxide,
 };
 use vorpal_sdk::{
+    api::artifact::ArtifactSystem,
     artifact::{gh::Gh, git::Git, nodejs::NodeJS},
     context::ConfigContext,
 };
 
-pub async fn build(context: &mut ConfigContext) -> Result<Vec<String>> {
-    let harnesses = vec![Pi::new().build(context).await?];
-
-    let languages = vec![NodeJS::new().build(context).await?];
-
-    let providers = vec![
-        Awscli2::new().build(context).await?,
-        Doppler::new().build(context).await?,
-        Gh::new().build(context).await?,
-        Kubectl::new().build(context).await?,
-        Op::new().build(context).await?,
-        Terraform::new().build(context).await?,
-    ];
-
-    let utilities = vec![
-        Delta::new().build(context).await?,
-        Direnv::new().build(context).await?,
-        Fd::new().build(context).await?,
-        Fzf::new().build(context).await?,
-        Git::new().build(context).await?,
-        Gum::new().build(context).await?,
-        Herdr::new().build(context).await?,
-        Hunk::new().build(context).await?,
-        Jj::new().build(context).await?,
-        Jq::new().build(context).await?,
-        Just::new().build(context).await?,
-        Lazygit::new().build(context).await?,
-        Nnn::new().build(context).await?,
-        Ripgrep::new().build(context).await?,
-        Sesh::new().build(context).await?,
-        Starship::new().build(context).await?,
-        Tmux::new().build(context).await?,
-        Zoxide::new().build(context).await?,
-    ];
-
-    Ok(harnesses
-        .into_iter()
-        .chain(languages)
-        .chain(providers)
-        .chain(utilities)
-        .collect())
+/// What a tool is for. Profiles select whole categories.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Category {
+    Harness,
+    Language,
+    Provider,
+    /// What a working shell needs: the prompt, search, git and the like.
+    Shell,
+    Utility,
+}
+
+/// A named selection of catalog categories. `Workstation` is everything; the
+/// smaller profiles are for machines that only need a working shell.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Profile {
+    Minimal,
+    #[default]
+    Workstation,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Tool {
+    Awscli2,
+    Delta,
+    Direnv,
+    Doppler,
+    Fd,
+    Fzf,
+    Gh,
+    Git,
+    Gum,
+    Herdr,
+    Hunk,
+    Jj,
+    Jq,
+    Just,
+    Kubectl,
+    Lazygit,
+    Nnn,
+    NodeJS,
+    Op,
+    Pi,
+    Ripgrep,
+    Sesh,
+    Starship,
+    Terraform,
+    Tmux,
+    Zoxide,
+}
+
+pub struct CatalogEntry {
+    pub category: Category,
+    pub description: &'static str,
+    pub name: &'static str,
+    pub systems: &'static [ArtifactSystem],
+    pub tool: Tool,
+}
+
+/// Every standalone tool on `PATH`, in name order. Tools that come with a
+/// component's configuration (bat, k9s, gopls) are built by that component.
+pub const CATALOG: &[CatalogEntry] = &[
+    CatalogEntry {
+        category: Category::Provider,
+        description: "AWS command-line interface",
+        name: "awscli2",
+        systems: &SYSTEMS,
+        tool: Tool::Awscli2,
+    },
+    CatalogEntry {
+        category: Category::Shell,
+        description: "Syntax-highlighting pager for git diffs",
+        name: "delta",
+        systems: &SYSTEMS,
+        tool: Tool::Delta,
+    },
+    CatalogEntry {
+        category: Category::Shell,
+        description: "Per-directory environment variables",
+        name: "direnv",
+        systems: &SYSTEMS,
+        tool: Tool::Direnv,
+    },
+    CatalogEntry {
+        category: Category::Provider,
+        description: "Secrets management",
+        name: "doppler",
+        systems: &SYSTEMS,
+        tool: Tool::Doppler,
+    },
+    CatalogEntry {
+        category: Category::Shell,
+        description: "Fast file finder",
+        name: "fd",
+        systems: &SYSTEMS,
+        tool: Tool::Fd,
+    },
+    CatalogEntry {
+        category: Category::Shell,
+        description: "Fuzzy finder",
+        name: "fzf",
+        systems: &SYSTEMS,
+        tool: Tool::Fzf,
+    },
+    CatalogEntry {
+        category: Category::Provider,
+        description: "GitHub CLI",
+        name: "gh",
+        systems: &SYSTEMS,
+        tool: Tool::Gh,
+    },
+    CatalogEntry {
+        category: Category::Shell,
+        description: "Version control",
+        name: "git",
+        systems: &SYSTEMS,
+        tool: Tool::Git,
+    },
+    CatalogEntry {
+        category: Category::Utility,
+        description: "Prompts and styling for shell scripts",
+        name: "gum",
+        systems: &SYSTEMS,
+        tool: Tool::Gum,
+    },
+    CatalogEntry {
+        category: Category::Utility,
+        description: "Terminal workspace for coding agents",
+        name: "herdr",
+        systems: &SYSTEMS,
+        tool: Tool::Herdr,
+    },
+    CatalogEntry {
+        category: Category::Utility,
+        description: "Terminal diff viewer",
+        name: "hunk",
+        systems: &SYSTEMS,
+        tool: Tool::Hunk,
+    },
+    CatalogEntry {
+        category: Category::Utility,
+        description: "Git-compatible VCS",
+        name: "jj",
+        systems: &SYSTEMS,
+        tool: Tool::Jj,
+    },
+    CatalogEntry {
+        category: Category::Shell,
+        description: "JSON processor",
+        name: "jq",
+        systems: &SYSTEMS,
+        tool: Tool::Jq,
+    },
+    CatalogEntry {
+        category: Category::Shell,
+        description: "Command runner",
+        name: "just",
+        systems: &SYSTEMS,
+        tool: Tool::Just,
+    },
+    CatalogEntry {
+        category: Category::Provider,
+        description: "Kubernetes CLI",
+        name: "kubectl",
+        systems: &SYSTEMS,
+        tool: Tool::Kubectl,
+    },
+    CatalogEntry {
+        category: Category::Utility,
+        description: "Terminal UI for git",
+        name: "lazygit",
+        systems: &SYSTEMS,
+        tool: Tool::Lazygit,
+    },
+    CatalogEntry {
+        category: Category::Utility,
+        description: "Terminal file manager",
+        name: "nnn",
+        systems: &SYSTEMS,
+        tool: Tool::Nnn,
+    },
+    CatalogEntry {
+        category: Category::Language,
+        description: "JavaScript runtime",
+        name: "nodejs",
+        systems: &SYSTEMS,
+        tool: Tool::NodeJS,
+    },
+    CatalogEntry {
+        category: Category::Provider,
+        description: "1Password CLI",
+        name: "op",
+        systems: &SYSTEMS,
+        tool: Tool::Op,
+    },
+    CatalogEntry {
+        category: Category::Harness,
+        description: "Coding agent harness",
+        name: "pi",
+        systems: &SYSTEMS,
+        tool: Tool::Pi,
+    },
+    CatalogEntry {
+        category: Category::Shell,
+        description: "Fast recursive search",
+        name: "ripgrep",
+        systems: &SYSTEMS,
+        tool: Tool::Ripgrep,
+    },
+    CatalogEntry {
+        category: Category::Utility,
+        description: "tmux session manager",
+        name: "sesh",
+        systems: &SYSTEMS,
+        tool: Tool::Sesh,
+    },
+    CatalogEntry {
+        category: Category::Shell,
+        description: "Shell prompt",
+        name: "starship",
+        systems: &SYSTEMS,
+        tool: Tool::Starship,
+    },
+    CatalogEntry {
+        category: Category::Provider,
+        description: "Infrastructure as code",
+        name: "terraform",
+        systems: &SYSTEMS,
+        tool: Tool::Terraform,
+    },
+    CatalogEntry {
+        category: Category::Shell,
+        description: "Terminal multiplexer",
+        name: "tmux",
+        systems: &SYSTEMS,
+        tool: Tool::Tmux,
+    },
+    CatalogEntry {
+        category: Category::Shell,
+        description: "Smarter cd",
+        name: "zoxide",
+        systems: &SYSTEMS,
+        tool: Tool::Zoxide,
+    },
+];
+
+impl Category {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Category::Harness => "harness",
+            Category::Language => "language",
+            Category::Provider => "provider",
+            Category::Shell => "shell",
+            Category::Utility => "utility",
+        }
+    }
+}
+
+impl Profile {
+    pub fn categories(self) -> &'static [Category] {
+        match self {
+            Profile::Minimal => &[Category::Shell],
+            Profile::Workstation => &[
+                Category::Harness,
+                Category::Language,
+                Category::Provider,
+                Category::Shell,
+                Category::Utility,
+            ],
+        }
+    }
+}
+
+impl CatalogEntry {
+    /// Whether to build the entry: the profile includes its category or the
+    /// extra `tools` name it, and it supports the system.
+    pub fn is_selected(&self, profile: Profile, tools: &[String], system: ArtifactSystem) -> bool {
+        let wanted = profile.categories().contains(&self.category)
+            || tools.iter().any(|tool| tool == self.name);
+
+        wanted && self.systems.contains(&system)
+    }
+}
+
+impl Tool {
+    async fn build(self, context: &mut ConfigContext) -> Result<String> {
+        match self {
+            Tool::Awscli2 => Awscli2::new().build(context).await,
+            Tool::Delta => Delta::new().build(context).await,
+            Tool::Direnv => Direnv::new().build(context).await,
+            Tool::Doppler => Doppler::new().build(context).await,
+            Tool::Fd => Fd::new().build(context).await,
+            Tool::Fzf => Fzf::new().build(context).await,
+            Tool::Gh => Gh::new().build(context).await,
+            Tool::Git => Git::new().build(context).await,
+            Tool::Gum => Gum::new().build(context).await,
+            Tool::Herdr => Herdr::new().build(context).await,
+            Tool::Hunk => Hunk::new().build(context).await,
+            Tool::Jj => Jj::new().build(context).await,
+            Tool::Jq => Jq::new().build(context).await,
+            Tool::Just => Just::new().build(context).await,
+            Tool::Kubectl => Kubectl::new().build(context).await,
+            Tool::Lazygit => Lazygit::new().build(context).await,
+            Tool::Nnn => Nnn::new().build(context).await,
+            Tool::NodeJS => NodeJS::new().build(context).await,
+            Tool::Op => Op::new().build(context).await,
+            Tool::Pi => Pi::new().build(context).await,
+            Tool::Ripgrep => Ripgrep::new().build(context).await,
+            Tool::Sesh => Sesh::new().build(context).await,
+            Tool::Starship => Starship::new().build(context).await,
+            Tool::Terraform => Terraform::new().build(context).await,
+            Tool::Tmux => Tmux::new().build(context).await,
+            Tool::Zoxide => Zoxide::new().build(context).await,
+        }
+    }
+}
+
+/// The README's tool table, one row per entry in catalog order. A test keeps
+/// the README in step with the catalog.
+pub fn readme_table() -> String {
+    let mut table =
+        String::from("| Tool | Category | Description |\n|------|----------|-------------|\n");
+
+    for entry in CATALOG {
+        table.push_str(&format!(
+            "| {} | {} | {} |\n",
+            entry.name,
+            entry.category.as_str(),
+            entry.description
+        ));
+    }
+
+    table
+}
+
+/// Builds every catalog entry the profile includes, and every entry named in
+/// `tools`, on the system being evaluated, in catalog order.
+///
+/// The entries are independent, but each builder holds the context's
+/// `&mut ConfigContext` until it returns, so they are resolved one at a
+/// time. Catalog order, not the order of `tools`, decides the digests'
+/// order.
+pub async fn build(
+    context: &mut impl BuildContext,
+    profile: Profile,
+    tools: &[String],
+) -> Result<Vec<String>> {
+    let system = context.get_system();
+
+    let mut artifacts = vec![];
+
+    for entry in CATALOG {
+        if entry.is_selected(profile, tools, system) {
+            let artifact = context
+                .external(entry.name, |context| Box::pin(entry.tool.build(context)))
+                .await?;
+
+            artifacts.push(artifact);
+        }
+    }
+
+    Ok(artifacts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build, readme_table, Category, Profile, CATALOG};
+    use crate::context::mock::MockContext;
+    use vorpal_sdk::api::artifact::ArtifactSystem::X8664Linux;
+
+    #[test]
+    fn catalog_is_in_name_order_without_duplicates() {
+        let names: Vec<_> = CATALOG.iter().map(|entry| entry.name).collect();
+
+        assert!(names.windows(2).all(|pair| pair[0] < pair[1]), "{names:?}");
+    }
+
+    #[test]
+    fn every_entry_belongs_to_the_workstation_profile() {
+        assert!(CATALOG
+            .iter()
+            .all(|entry| Profile::Workstation.categories().contains(&entry.category)));
+    }
+
+    #[test]
+    fn minimal_profile_selects_only_shell_tools() {
+        let minimal: Vec<_> = CATALOG
+            .iter()
+            .filter(|entry| entry.is_selected(Profile::Minimal, &[], X8664Linux))
+            .collect();
+
+        assert!(minimal.iter().any(|entry| entry.name == "git"));
+        assert!(minimal
+            .iter()
+            .all(|entry| entry.category == Category::Shell));
+        assert!(!minimal.iter().any(|entry| entry.name == "awscli2"));
+    }
+
+    #[test]
+    fn readme_tool_table_matches_the_catalog() {
+        let readme = include_str!("../../README.md");
+
+        assert!(
+            readme.contains(&readme_table()),
+            "README tool table is out of date; replace it with:\n{}",
+            readme_table()
+        );
+    }
+
+    #[test]
+    fn extra_tools_are_selected_outside_their_profiles() {
+        let awscli2 = CATALOG
+            .iter()
+            .find(|entry| entry.name == "awscli2")
+            .unwrap();
+
+        assert!(!awscli2.is_selected(Profile::Minimal, &[], X8664Linux));
+        assert!(awscli2.is_selected(Profile::Minimal, &["awscli2".to_string()], X8664Linux));
+    }
+
+    #[tokio::test]
+    async fn artifacts_come_back_in_catalog_order() {
+        let mut context = MockContext::new(X8664Linux);
+        let tools = ["terraform".to_string(), "awscli2".to_string()];
+
+        let artifacts = build(&mut context, Profile::Minimal, &tools).await.unwrap();
+
+        let expected: Vec<_> = CATALOG
+            .iter()
+            .filter(|entry| entry.is_selected(Profile::Minimal, &tools, X8664Linux))
+            .map(|entry| entry.name.to_string())
+            .collect();
+
+        assert_eq!(artifacts, expected);
+        assert_eq!(artifacts.first().map(String::as_str), Some("awscli2"));
+        assert!(artifacts.contains(&"terraform".to_string()));
+    }
 }
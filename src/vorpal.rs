@@ -1,18 +1,49 @@
 use anyhow::Result;
-use dotfiles::{user::UserEnvironment, SYSTEMS};
+use dotfiles::{
+    context::{BuildContext, VorpalContext},
+    graph::GRAPH_VAR,
+    selection::{Build, Selection},
+    store::Store,
+    user::UserEnvironment,
+};
+use std::{env, path::PathBuf};
 use vorpal_sdk::{artifact::language::rust::RustDevelopmentEnvironment, context::get_context};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let context = &mut get_context().await?;
+    let mut context = VorpalContext::new(get_context().await?);
 
-    RustDevelopmentEnvironment::new("dev", SYSTEMS.to_vec())
-        .build(context)
-        .await?;
+    let selection = Selection::from_env()?;
 
-    UserEnvironment::new("user", SYSTEMS.to_vec())
-        .build(context)
-        .await?;
+    if selection.builds(Build::Dev) {
+        let systems = selection.systems.clone();
+
+        context
+            .external("dev", |context| {
+                Box::pin(RustDevelopmentEnvironment::new("dev", systems).build(context))
+            })
+            .await?;
+    }
+
+    if selection.builds(Build::User) {
+        let mut user = UserEnvironment::new(selection.user_name(), selection.systems.clone())
+            .with_catalog(selection.catalog)
+            .with_store(Store::from_env());
+
+        if let Some(components) = selection.components {
+            user = user.with_components(components);
+        }
+
+        if let Some(profile) = selection.profile {
+            user = user.with_profile(profile);
+        }
+
+        user.build(&mut context).await?;
+    }
+
+    if let Some(dir) = env::var_os(GRAPH_VAR) {
+        context.graph().write(&PathBuf::from(dir))?;
+    }
 
     context.run().await
 }
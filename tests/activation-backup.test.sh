@@ -0,0 +1,300 @@
+#!/bin/bash
+
+# Behavior suite for `dotfiles-activate` (src/user/activation_script.sh):
+# backup, activate and restore, and the collection of links a later build
+# dropped, which runs each activation against the previous one's manifest.
+#
+# Wired into CI: `.github/workflows/vorpal.yaml` enumerates test files by name
+# and this one is in that list.
+#
+# SEAM: the script body is run behind a header like the one the build
+# prepends, naming a temp store and reading `manifest` from a file each case
+# writes, with HOME pointed at a temp directory — no Vorpal, no store, and
+# nothing outside the sandbox is touched.
+
+set -uo pipefail
+
+SCRIPT_DIR=$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)
+REPO_ROOT=$(cd "${SCRIPT_DIR}/.." && pwd)
+BODY="${ACTIVATION_SCRIPT:-${REPO_ROOT}/src/user/activation_script.sh}"
+
+PASS=0
+FAIL=0
+
+fail() {
+    printf 'FAIL: %s\n' "$1" >&2
+    FAIL=$((FAIL + 1))
+}
+
+pass() {
+    printf 'PASS: %s\n' "$1"
+    PASS=$((PASS + 1))
+}
+
+fatal() {
+    printf 'FATAL: %s\n' "$1" >&2
+    exit 2
+}
+
+[ -f "$BODY" ] || fatal "activation script not found at ${BODY}"
+
+SANDBOX=$(mktemp -d "${TMPDIR:-/tmp}/activation-backup-test.XXXXXX") || fatal "mktemp failed"
+trap 'chmod -R u+w "$SANDBOX"; rm -rf "$SANDBOX"' EXIT
+
+SCRIPT="${SANDBOX}/dotfiles-activate"
+{
+    printf '#!/usr/bin/env bash\nset -euo pipefail\n\n'
+    printf 'store="%s"\n' "${SANDBOX}/store"
+    printf "placeholder='@HOME@'\n"
+    printf 'partial="${PARTIAL:-false}"\n'
+    printf 'manifest() {\n    cat "$MANIFEST"\n}\n\n'
+    cat "$BODY"
+} >"$SCRIPT"
+
+STDOUT_FILE="${SANDBOX}/activate.stdout"
+STDERR_FILE="${SANDBOX}/activate.stderr"
+
+# Fresh HOME and store per case; the manifest starts empty.
+reset() {
+    chmod -R u+w "${SANDBOX}/home" "${SANDBOX}/store" 2>/dev/null
+    rm -rf "${SANDBOX}/home" "${SANDBOX}/store"
+    mkdir -p "${SANDBOX}/home" "${SANDBOX}/store"
+    : >"${SANDBOX}/manifest"
+}
+
+# store_file <relative path> <content>: a file in the fake store.
+store_file() {
+    mkdir -p "$(dirname "${SANDBOX}/store/$1")"
+    printf '%s\n' "$2" >"${SANDBOX}/store/$1"
+}
+
+# install <mode> <store path> <target>: one manifest line.
+install() {
+    printf '%s\t%s\t%s\n' "$1" "${SANDBOX}/store/$2" "$3" >>"${SANDBOX}/manifest"
+}
+
+run() {
+    HOME="${SANDBOX}/home" MANIFEST="${SANDBOX}/manifest" \
+        bash "$SCRIPT" "$@" >"$STDOUT_FILE" 2>"$STDERR_FILE"
+}
+
+backups() {
+    ls -1 "${SANDBOX}/home/.local/state/dotfiles/backup" 2>/dev/null | wc -l | tr -d ' '
+}
+
+case_foreign_file_is_backed_up_and_restored() {
+    local name="a file no activation wrote is backed up, then restored"
+    reset
+    store_file aaa/config 'from the store'
+    install copy aaa/config .docket/config
+    mkdir -p "${SANDBOX}/home/.docket"
+    printf 'mine\n' >"${SANDBOX}/home/.docket/config"
+
+    run activate || { fail "$name: activate exited non-zero"; return; }
+    if [ "$(cat "${SANDBOX}/home/.docket/config")" != 'from the store' ] || [ "$(backups)" != 1 ]; then
+        fail "$name: expected the store copy in place and one backup"
+        return
+    fi
+
+    run restore || { fail "$name: restore exited non-zero"; return; }
+    if [ "$(cat "${SANDBOX}/home/.docket/config")" = mine ] && [ "$(backups)" = 0 ]; then
+        pass "$name"
+    else
+        fail "$name: expected the original file back and no backup left"
+    fi
+}
+
+case_edited_copy_is_backed_up() {
+    local name="a writable copy edited since the last activation is backed up"
+    reset
+    store_file aaa/settings.json '{"model": "default"}'
+    install copy-writable aaa/settings.json .claude/settings.json
+    run activate || { fail "$name: first activate exited non-zero"; return; }
+    printf '{"model": "edited"}\n' >"${SANDBOX}/home/.claude/settings.json"
+
+    : >"${SANDBOX}/manifest"
+    store_file bbb/settings.json '{"model": "newer"}'
+    install copy-writable bbb/settings.json .claude/settings.json
+    run activate || { fail "$name: second activate exited non-zero"; return; }
+
+    local saved
+    saved=$(cat "${SANDBOX}"/home/.local/state/dotfiles/backup/*/files/.claude/settings.json 2>/dev/null)
+    if [ "$saved" = '{"model": "edited"}' ] &&
+        [ "$(cat "${SANDBOX}/home/.claude/settings.json")" = '{"model": "newer"}' ]; then
+        pass "$name"
+    else
+        fail "$name: expected the edit in a backup and the new copy in place"
+    fi
+}
+
+case_unchanged_copy_is_replaced_without_backup() {
+    local name="a copy left as the last activation wrote it is replaced without a backup"
+    reset
+    store_file aaa/config 'first'
+    install copy aaa/config .docket/config
+    run activate || { fail "$name: first activate exited non-zero"; return; }
+
+    : >"${SANDBOX}/manifest"
+    store_file bbb/config 'second'
+    install copy bbb/config .docket/config
+    run activate || { fail "$name: second activate exited non-zero"; return; }
+
+    if [ "$(cat "${SANDBOX}/home/.docket/config")" = second ] && [ "$(backups)" = 0 ]; then
+        pass "$name"
+    else
+        fail "$name: expected the new copy in place and no backup"
+    fi
+}
+
+case_edited_render_is_backed_up() {
+    local name="a rendered file edited since the last activation is backed up"
+    reset
+    store_file aaa/env 'GOPATH=@HOME@/go'
+    install render aaa/env .config/go/env
+    run activate || { fail "$name: first activate exited non-zero"; return; }
+    if [ "$(cat "${SANDBOX}/home/.config/go/env")" != "GOPATH=${SANDBOX}/home/go" ]; then
+        fail "$name: expected the placeholder expanded to HOME"
+        return
+    fi
+
+    run activate || { fail "$name: second activate exited non-zero"; return; }
+    if [ "$(backups)" != 0 ]; then
+        fail "$name: an untouched render was backed up"
+        return
+    fi
+
+    printf 'GOPATH=/elsewhere\n' >"${SANDBOX}/home/.config/go/env"
+    run activate || { fail "$name: third activate exited non-zero"; return; }
+    local saved
+    saved=$(cat "${SANDBOX}"/home/.local/state/dotfiles/backup/*/files/.config/go/env 2>/dev/null)
+    if [ "$saved" = 'GOPATH=/elsewhere' ]; then
+        pass "$name"
+    else
+        fail "$name: expected the edit in a backup"
+    fi
+}
+
+case_store_link_is_not_backed_up() {
+    local name="a link into the store is replaced without a backup"
+    reset
+    store_file aaa/config 'linked'
+    store_file bbb/config 'copied'
+    install copy bbb/config .docket/config
+    mkdir -p "${SANDBOX}/home/.docket"
+    ln -s "${SANDBOX}/store/aaa/config" "${SANDBOX}/home/.docket/config"
+
+    run activate || { fail "$name: activate exited non-zero"; return; }
+    if [ ! -L "${SANDBOX}/home/.docket/config" ] &&
+        [ "$(cat "${SANDBOX}/home/.docket/config")" = copied ] && [ "$(backups)" = 0 ]; then
+        pass "$name"
+    else
+        fail "$name: expected the copy in place and no backup"
+    fi
+}
+
+case_restore_without_backup_fails() {
+    local name="restore with nothing backed up exits non-zero"
+    reset
+    if run restore; then
+        fail "$name: restore succeeded"
+    elif grep -q 'no backup' "$STDERR_FILE"; then
+        pass "$name"
+    else
+        fail "$name: expected a 'no backup' message"
+    fi
+}
+
+# first_generation: activates a build that links .config/bat/config, as
+# Vorpal's activation would, then swaps in a build that no longer does.
+first_generation() {
+    reset
+    store_file aaa/config 'linked'
+    store_file bbb/config 'copied'
+    install link aaa/config .config/bat/config
+    install copy bbb/config .docket/config
+    mkdir -p "${SANDBOX}/home/.config/bat"
+    ln -s "${SANDBOX}/store/aaa/config" "${SANDBOX}/home/.config/bat/config"
+    run activate || return 1
+    : >"${SANDBOX}/manifest"
+    install copy bbb/config .docket/config
+}
+
+case_gc_removes_stale_store_link() {
+    local name="gc removes a link into the store that the new build dropped"
+    first_generation || { fail "$name: first activate exited non-zero"; return; }
+
+    run activate || { fail "$name: second activate exited non-zero"; return; }
+    if [ ! -e "${SANDBOX}/home/.config/bat/config" ] && [ ! -L "${SANDBOX}/home/.config/bat/config" ] &&
+        grep -q 'removed stale link ~/.config/bat/config' "$STDOUT_FILE" &&
+        [ "$(cat "${SANDBOX}/home/.docket/config")" = copied ]; then
+        pass "$name"
+    else
+        fail "$name: expected the link gone, the copy kept and a 'removed stale link' line"
+    fi
+}
+
+case_gc_leaves_foreign_file() {
+    local name="gc leaves a real file at a dropped target and says so"
+    first_generation || { fail "$name: first activate exited non-zero"; return; }
+    rm "${SANDBOX}/home/.config/bat/config"
+    printf 'mine\n' >"${SANDBOX}/home/.config/bat/config"
+
+    run activate || { fail "$name: second activate exited non-zero"; return; }
+    if [ "$(cat "${SANDBOX}/home/.config/bat/config")" = mine ] &&
+        grep -q '~/.config/bat/config is no longer installed; left in place' "$STDERR_FILE"; then
+        pass "$name"
+    else
+        fail "$name: expected the file kept and a 'left in place' message"
+    fi
+}
+
+case_gc_leaves_link_outside_store() {
+    local name="gc leaves a link outside the store at a dropped target and says so"
+    first_generation || { fail "$name: first activate exited non-zero"; return; }
+    printf 'elsewhere\n' >"${SANDBOX}/elsewhere"
+    ln -sfn "${SANDBOX}/elsewhere" "${SANDBOX}/home/.config/bat/config"
+
+    run activate || { fail "$name: second activate exited non-zero"; return; }
+    if [ -L "${SANDBOX}/home/.config/bat/config" ] &&
+        [ "$(readlink "${SANDBOX}/home/.config/bat/config")" = "${SANDBOX}/elsewhere" ] &&
+        grep -q '~/.config/bat/config is no longer installed; left in place' "$STDERR_FILE"; then
+        pass "$name"
+    else
+        fail "$name: expected the link kept and a 'left in place' message"
+    fi
+}
+
+case_partial_build_neither_collects_nor_records() {
+    local name="a partial build leaves dropped links and the last record alone"
+    first_generation || { fail "$name: first activate exited non-zero"; return; }
+    local recorded
+    recorded=$(cat "${SANDBOX}/home/.local/state/dotfiles/manifest")
+
+    PARTIAL=true run activate || { fail "$name: partial activate exited non-zero"; return; }
+    if [ -L "${SANDBOX}/home/.config/bat/config" ] &&
+        [ "$(cat "${SANDBOX}/home/.local/state/dotfiles/manifest")" = "$recorded" ] &&
+        [ "$(cat "${SANDBOX}/home/.docket/config")" = copied ]; then
+        pass "$name"
+    else
+        fail "$name: expected the link kept, the copy in place and the record unchanged"
+    fi
+}
+
+case_foreign_file_is_backed_up_and_restored
+case_edited_copy_is_backed_up
+case_unchanged_copy_is_replaced_without_backup
+case_edited_render_is_backed_up
+case_store_link_is_not_backed_up
+case_restore_without_backup_fails
+case_gc_removes_stale_store_link
+case_gc_leaves_foreign_file
+case_gc_leaves_link_outside_store
+case_partial_build_neither_collects_nor_records
+
+printf '\n%d passed, %d failed\n' "$PASS" "$FAIL"
+
+if [ "$FAIL" -ne 0 ]; then
+    exit 1
+fi
+
+exit 0
@@ -0,0 +1,3 @@
+set -gx EDITOR "nvim"
+set -gx GOPATH "$HOME/Development/language/go"
+set -gx PATH "/Applications/VMware Fusion.app/Contents/Library" "$HOME/Development/language/go/bin" "$HOME/.vorpal/bin" "$HOME/.local/bin" $PATH
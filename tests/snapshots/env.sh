@@ -0,0 +1,3 @@
+export EDITOR="nvim"
+export GOPATH="${HOME}/Development/language/go"
+export PATH="/Applications/VMware Fusion.app/Contents/Library:${HOME}/Development/language/go/bin:${HOME}/.vorpal/bin:${HOME}/.local/bin:${PATH}"
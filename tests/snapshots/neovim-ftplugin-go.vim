@@ -0,0 +1,2 @@
+setlocal formatprg=gofmt
+setlocal noexpandtab shiftwidth=4 tabstop=4
\ No newline at end of file
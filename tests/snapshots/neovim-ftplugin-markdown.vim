@@ -0,0 +1,3 @@
+setlocal spell
+setlocal textwidth=80
+setlocal wrap
\ No newline at end of file
@@ -0,0 +1 @@
+setlocal expandtab shiftwidth=2 softtabstop=2
\ No newline at end of file